@@ -0,0 +1,112 @@
+use crate::database_new::Database;
+use actix_web::{get, post, web, App, HttpResponse, HttpServer, Responder};
+use log::{error, info};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// HTTP front door for the crawler's data, in the spirit of the stats/search APIs that
+/// MeiliSearch and Plume put in front of their own storage engines.
+pub struct ApiServer {
+    database: Arc<Database>,
+    bind_addr: String,
+}
+
+impl ApiServer {
+    pub fn new(database: Arc<Database>, bind_addr: impl Into<String>) -> Self {
+        Self {
+            database,
+            bind_addr: bind_addr.into(),
+        }
+    }
+
+    pub async fn start(&self) -> std::io::Result<()> {
+        info!("Starting API server on {}", self.bind_addr);
+        let database = self.database.clone();
+
+        HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(database.clone()))
+                .service(get_stats)
+                .service(get_recent_crawls)
+                .service(search)
+                .service(add_seeds)
+                .service(get_backlinks)
+        })
+        .bind(&self.bind_addr)?
+        .run()
+        .await
+    }
+}
+
+#[get("/stats")]
+async fn get_stats(db: web::Data<Arc<Database>>) -> impl Responder {
+    match db.get_dashboard_stats().await {
+        Ok(stats) => HttpResponse::Ok().json(stats),
+        Err(e) => {
+            error!("Failed to fetch dashboard stats: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({ "error": "failed to fetch stats" }))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RecentCrawlsQuery {
+    limit: Option<i32>,
+}
+
+#[get("/crawls/recent")]
+async fn get_recent_crawls(db: web::Data<Arc<Database>>, query: web::Query<RecentCrawlsQuery>) -> impl Responder {
+    let limit = query.limit.unwrap_or(50);
+    match db.get_recent_crawls(limit).await {
+        Ok(crawls) => HttpResponse::Ok().json(crawls),
+        Err(e) => {
+            error!("Failed to fetch recent crawls: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({ "error": "failed to fetch recent crawls" }))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    q: String,
+    limit: Option<usize>,
+}
+
+#[get("/search")]
+async fn search(db: web::Data<Arc<Database>>, query: web::Query<SearchQuery>) -> impl Responder {
+    let limit = query.limit.unwrap_or(20);
+    match db.search(&query.q, limit).await {
+        Ok(results) => HttpResponse::Ok().json(results),
+        Err(e) => {
+            error!("Search failed for query '{}': {}", query.q, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({ "error": "search failed" }))
+        }
+    }
+}
+
+#[post("/seeds")]
+async fn add_seeds(db: web::Data<Arc<Database>>, urls: web::Json<Vec<String>>) -> impl Responder {
+    match db.add_seed_urls(&urls).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "added": urls.len() })),
+        Err(e) => {
+            error!("Failed to add seed URLs: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({ "error": "failed to add seed urls" }))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BacklinksQuery {
+    target: String,
+}
+
+#[get("/backlinks")]
+async fn get_backlinks(db: web::Data<Arc<Database>>, query: web::Query<BacklinksQuery>) -> impl Responder {
+    match db.get_backlinks_for_target(&query.target).await {
+        Ok(backlinks) => HttpResponse::Ok().json(backlinks),
+        Err(e) => {
+            error!("Failed to fetch backlinks for '{}': {}", query.target, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({ "error": "failed to fetch backlinks" }))
+        }
+    }
+}
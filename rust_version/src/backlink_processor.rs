@@ -1,21 +1,344 @@
 use reqwest::Client;
 use scraper::{Html, Selector};
-use std::collections::HashSet;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use url::{Url, ParseError};
 use crate::models::*;
 use crate::database::BacklinkDatabase;
 use regex::Regex;
+use futures::future::join_all;
+use tokio::sync::{Mutex, Semaphore};
+
+// Labeled sample texts (`true` = spam) that `SpamClassifier::new` trains on as its default
+// training set; real training data should come from labeled history.
+const DEFAULT_SPAM_SAMPLES: &[(&str, bool)] = &[
+    ("casino online free spins jackpot", true),
+    ("viagra pharmacy cheap pills discount", true),
+    ("payday loan fast cash approval", true),
+    ("make money fast work from home", true),
+    ("weight loss miracle pills", true),
+    ("adult dating singles near you", true),
+    ("poker texas holdem real money", true),
+    ("insurance quotes compare rates", true),
+    ("read our documentation and getting started guide", false),
+    ("official blog post about product updates", false),
+    ("see our github repository for the source code", false),
+    ("contact us for support and pricing", false),
+    ("research paper on machine learning", false),
+    ("community forum discussion thread", false),
+];
+
+/// Naive Bayes bag-of-words classifier trained on anchor text, link context and source
+/// URL, used to score backlinks as spam instead of matching a fixed keyword list.
+#[derive(Clone)]
+pub struct SpamClassifier {
+    spam_word_counts: HashMap<String, u64>,
+    ham_word_counts: HashMap<String, u64>,
+    spam_docs: u64,
+    ham_docs: u64,
+}
+
+impl SpamClassifier {
+    pub fn new() -> Self {
+        let mut classifier = Self {
+            spam_word_counts: HashMap::new(),
+            ham_word_counts: HashMap::new(),
+            spam_docs: 0,
+            ham_docs: 0,
+        };
+        for (text, is_spam) in DEFAULT_SPAM_SAMPLES {
+            classifier.train(text, *is_spam);
+        }
+        classifier
+    }
+
+    fn tokenize(text: &str) -> Vec<String> {
+        text.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_string())
+            .collect()
+    }
+
+    /// Incorporates one labeled example into the model. Safe to call repeatedly as more
+    /// confirmed spam/ham backlinks are discovered.
+    pub fn train(&mut self, text: &str, is_spam: bool) {
+        let (counts, docs) = if is_spam {
+            (&mut self.spam_word_counts, &mut self.spam_docs)
+        } else {
+            (&mut self.ham_word_counts, &mut self.ham_docs)
+        };
+        *docs += 1;
+        for word in Self::tokenize(text) {
+            *counts.entry(word).or_insert(0) += 1;
+        }
+    }
+
+    /// Returns `P(spam | text)` using Laplace-smoothed word likelihoods.
+    pub fn spam_probability(&self, text: &str) -> f64 {
+        if self.spam_docs == 0 || self.ham_docs == 0 {
+            return 0.0;
+        }
+
+        let total_docs = (self.spam_docs + self.ham_docs) as f64;
+        let mut log_spam = (self.spam_docs as f64 / total_docs).ln();
+        let mut log_ham = (self.ham_docs as f64 / total_docs).ln();
+
+        let spam_vocab: f64 = self.spam_word_counts.len() as f64;
+        let ham_vocab: f64 = self.ham_word_counts.len() as f64;
+        let spam_total: f64 = self.spam_word_counts.values().sum::<u64>() as f64;
+        let ham_total: f64 = self.ham_word_counts.values().sum::<u64>() as f64;
+
+        for word in Self::tokenize(text) {
+            let spam_count = *self.spam_word_counts.get(&word).unwrap_or(&0) as f64;
+            let ham_count = *self.ham_word_counts.get(&word).unwrap_or(&0) as f64;
+            log_spam += ((spam_count + 1.0) / (spam_total + spam_vocab + 1.0)).ln();
+            log_ham += ((ham_count + 1.0) / (ham_total + ham_vocab + 1.0)).ln();
+        }
+
+        // Convert back from log-space via the logistic trick to avoid overflow on long texts.
+        let diff = log_ham - log_spam;
+        1.0 / (1.0 + diff.exp())
+    }
+}
+
+/// Minimum `P(spam)` for a backlink to be reported as spam by `detect_spam_backlinks`.
+const SPAM_PROBABILITY_THRESHOLD: f64 = 0.5;
+
+/// A backlink-discovery search engine: builds a query URL for a results page and parses
+/// that page's HTML back into `BacklinkData`. Implement this to add a new engine without
+/// touching `BacklinkProcessor`'s crawl loop.
+trait SearchEngine: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// How many result pages to fetch before giving up on an engine.
+    fn max_pages(&self) -> usize {
+        1
+    }
+
+    /// The URL for page `page` (0-indexed) of results for `target_url`.
+    fn search_url(&self, target_url: &str, page: usize) -> String;
+
+    /// Extracts backlinks from one fetched results page.
+    fn parse_results(&self, html: &str, target_url: &str) -> Result<Vec<BacklinkData>, CrawlError>;
+}
+
+struct GoogleSearchEngine;
+
+impl SearchEngine for GoogleSearchEngine {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    fn max_pages(&self) -> usize {
+        2
+    }
+
+    fn search_url(&self, target_url: &str, page: usize) -> String {
+        let query = format!("link:{}", target_url);
+        format!(
+            "https://www.google.com/search?q={}&num=100&start={}",
+            urlencoding::encode(&query),
+            page * 100
+        )
+    }
+
+    fn parse_results(&self, html: &str, target_url: &str) -> Result<Vec<BacklinkData>, CrawlError> {
+        parse_search_result_links(html, target_url, "google.com")
+    }
+}
+
+struct BingSearchEngine;
+
+impl SearchEngine for BingSearchEngine {
+    fn name(&self) -> &'static str {
+        "bing"
+    }
+
+    fn max_pages(&self) -> usize {
+        2
+    }
+
+    fn search_url(&self, target_url: &str, page: usize) -> String {
+        let query = format!("linkfromdomain:{}", target_url);
+        format!(
+            "https://www.bing.com/search?q={}&count=50&first={}",
+            urlencoding::encode(&query),
+            page * 50 + 1
+        )
+    }
+
+    fn parse_results(&self, html: &str, target_url: &str) -> Result<Vec<BacklinkData>, CrawlError> {
+        parse_search_result_links(html, target_url, "bing.com")
+    }
+}
+
+/// Shared result parser for Google/Bing, whose search-result markup both reduce to "every
+/// `<a href>` that isn't pointing back at the engine itself".
+fn parse_search_result_links(html: &str, target_url: &str, engine_host: &str) -> Result<Vec<BacklinkData>, CrawlError> {
+    let document = Html::parse_document(html);
+    let link_selector = Selector::parse("a[href]").map_err(|_| CrawlError::ParseError("Invalid CSS selector".to_string()))?;
+
+    Ok(document.select(&link_selector)
+        .filter_map(|element| {
+            let href = element.value().attr("href")?;
+            if !href.starts_with("http") || href.contains(engine_host) {
+                return None;
+            }
+            Some(BacklinkData {
+                source_url: href.to_string(),
+                target_url: target_url.to_string(),
+                anchor_text: element.text().collect::<String>(),
+                context: String::new(),
+                page_title: String::new(),
+                domain_authority: 0.0,
+                is_nofollow: false,
+                crawl_date: chrono::Utc::now(),
+                threat_type: None,
+            })
+        })
+        .collect())
+}
+
+impl Default for SpamClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// Only `http`/`https` links are worth following; this drops `mailto:`, `javascript:`,
+/// `data:` and other schemes that would never resolve to a crawlable backlink source.
+fn is_supported_scheme(url: &Url) -> bool {
+    url.scheme() == "http" || url.scheme() == "https"
+}
+
+/// `noindex`/`nofollow` flags parsed from a page's combined `<meta name="robots">` and
+/// `X-Robots-Tag` directives.
+struct RobotsDirectives {
+    noindex: bool,
+    nofollow: bool,
+}
+
+impl RobotsDirectives {
+    fn parse(directives: &str) -> Self {
+        let lower = directives.to_ascii_lowercase();
+        Self {
+            noindex: lower.split(',').any(|d| d.trim() == "noindex"),
+            nofollow: lower.split(',').any(|d| d.trim() == "nofollow"),
+        }
+    }
+}
+
+/// What crawling one page for backlinks turned up: the backlinks it records, plus the
+/// other outgoing links worth adding to `discover_backlinks`'s frontier.
+struct PageCrawlResult {
+    backlinks: Vec<BacklinkData>,
+    outgoing_links: Vec<String>,
+}
+
+/// A per-host token bucket: each host starts with `capacity` tokens and refills at
+/// `refill_per_sec`, so a burst is allowed but the sustained rate to any one host is capped.
+struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        Self { tokens: capacity, capacity, refill_per_sec, last_refill: Instant::now() }
+    }
+
+    fn try_take(&mut self) -> bool {
+        let elapsed = self.last_refill.elapsed().as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = Instant::now();
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Shared rate limiter handing out one token per request per host, so concurrent crawl
+/// workers never hammer the same domain even though they hammer different ones in parallel.
+struct HostRateLimiter {
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    capacity: f64,
+    refill_per_sec: f64,
+}
+
+impl HostRateLimiter {
+    fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        Self { buckets: Mutex::new(HashMap::new()), capacity, refill_per_sec }
+    }
+
+    /// Blocks until a token is available for `host`, polling at a short fixed interval.
+    async fn acquire(&self, host: &str) {
+        loop {
+            {
+                let mut buckets = self.buckets.lock().await;
+                let bucket = buckets.entry(host.to_string())
+                    .or_insert_with(|| TokenBucket::new(self.capacity, self.refill_per_sec));
+                if bucket.try_take() {
+                    return;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
 
 pub struct BacklinkProcessor {
     client: Client,
     user_agent: String,
     timeout: Duration,
     max_redirects: usize,
+    spam_classifier: SpamClassifier,
+    search_engines: Arc<Vec<Box<dyn SearchEngine>>>,
+    /// When non-empty, only hosts matching one of these (or a subdomain of one) are
+    /// crawled; everything else is skipped before it enters `urls_to_check`.
+    allowed_domains: HashSet<String>,
+    /// Hosts (or their subdomains) that are never crawled, regardless of `allowed_domains`.
+    weed_domains: HashSet<String>,
+    /// Caps how many `crawl_page_for_backlinks` calls are in flight at once.
+    max_concurrency: usize,
+    /// Caps how many pages `discover_backlinks` will visit in total.
+    max_pages: usize,
+    semaphore: Arc<Semaphore>,
+    rate_limiter: Arc<HostRateLimiter>,
+}
+
+impl Clone for BacklinkProcessor {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            user_agent: self.user_agent.clone(),
+            timeout: self.timeout,
+            max_redirects: self.max_redirects,
+            spam_classifier: self.spam_classifier.clone(),
+            search_engines: self.search_engines.clone(),
+            allowed_domains: self.allowed_domains.clone(),
+            weed_domains: self.weed_domains.clone(),
+            max_concurrency: self.max_concurrency,
+            max_pages: self.max_pages,
+            semaphore: self.semaphore.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+        }
+    }
 }
 
 impl BacklinkProcessor {
-    pub fn new(user_agent: String, timeout_secs: u64, max_redirects: usize) -> Self {
+    pub fn new(user_agent: String, timeout_secs: u64, max_redirects: usize, max_concurrency: usize, max_pages: usize) -> Self {
         let client = Client::builder()
             .user_agent(&user_agent)
             .timeout(Duration::from_secs(timeout_secs))
@@ -28,85 +351,109 @@ impl BacklinkProcessor {
             user_agent,
             timeout: Duration::from_secs(timeout_secs),
             max_redirects,
+            spam_classifier: SpamClassifier::new(),
+            search_engines: Arc::new(vec![Box::new(GoogleSearchEngine), Box::new(BingSearchEngine)]),
+            allowed_domains: HashSet::new(),
+            weed_domains: HashSet::new(),
+            max_concurrency,
+            max_pages,
+            semaphore: Arc::new(Semaphore::new(max_concurrency)),
+            rate_limiter: Arc::new(HostRateLimiter::new(1.0, 0.5)),
         }
     }
 
-    pub async fn discover_backlinks(&self, target_url: &str) -> Result<Vec<BacklinkData>, CrawlError> {
-        let mut backlinks = Vec::new();
-        let mut visited_urls = HashSet::new();
-        let mut urls_to_check = vec![target_url.to_string()];
+    pub fn with_allowed_domains(mut self, domains: HashSet<String>) -> Self {
+        self.allowed_domains = domains;
+        self
+    }
 
-        // Get referring domains from search engines and other sources
-        let search_backlinks = self.get_search_engine_backlinks(target_url).await?;
-        backlinks.extend(search_backlinks);
+    pub fn with_weed_domains(mut self, domains: HashSet<String>) -> Self {
+        self.weed_domains = domains;
+        self
+    }
 
-        // Crawl for backlinks from discovered pages
-        while let Some(url) = urls_to_check.pop() {
-            if visited_urls.contains(&url) || visited_urls.len() >= 1000 {
-                continue;
-            }
+    /// Feeds a confirmed spam/ham backlink back into the classifier, e.g. after manual
+    /// review overrides an automated verdict.
+    pub fn train_spam_classifier(&mut self, backlink: &BacklinkData, is_spam: bool) {
+        let text = format!("{} {} {}", backlink.source_url, backlink.anchor_text, backlink.context);
+        self.spam_classifier.train(&text, is_spam);
+    }
 
-            visited_urls.insert(url.clone());
+    /// True when `url`'s host is allowed to be crawled under `allowed_domains`/`weed_domains`.
+    fn is_domain_allowed(&self, url: &str) -> bool {
+        let Ok(parsed) = Url::parse(url) else { return false };
+        let Some(host) = parsed.host_str() else { return false };
 
-            match self.crawl_page_for_backlinks(&url, target_url).await {
-                Ok(page_backlinks) => {
-                    for backlink in page_backlinks {
-                        if !backlinks.iter().any(|b| b.source_url == backlink.source_url) {
-                            backlinks.push(backlink);
-                        }
-                    }
-                }
-                Err(_) => continue, // Skip pages that can't be crawled
-            }
+        if self.weed_domains.iter().any(|d| host_matches(host, d)) {
+            return false;
         }
-
-        Ok(backlinks)
+        self.allowed_domains.is_empty() || self.allowed_domains.iter().any(|d| host_matches(host, d))
     }
 
-    async fn get_search_engine_backlinks(&self, target_url: &str) -> Result<Vec<BacklinkData>, CrawlError> {
+    /// Crawls the discovery frontier with up to `max_concurrency` pages in flight at
+    /// once (gated by `self.semaphore`), dispatching each round of fetches as its own
+    /// task and collecting their `PageCrawlResult`s back through an mpsc channel. Each
+    /// task also waits for its host's `rate_limiter` token, so concurrency doesn't turn
+    /// into hammering a single domain. Stops once `max_pages` URLs have been visited.
+    pub async fn discover_backlinks(&self, target_url: &str) -> Result<Vec<BacklinkData>, CrawlError> {
         let mut backlinks = Vec::new();
 
-        // Google search for backlinks
-        if let Ok(google_links) = self.search_google_backlinks(target_url).await {
-            backlinks.extend(google_links);
-        }
+        // Get referring domains from search engines and other sources
+        let search_backlinks = self.get_search_engine_backlinks(target_url).await?;
+        backlinks.extend(search_backlinks);
 
-        // Bing search for backlinks
-        if let Ok(bing_links) = self.search_bing_backlinks(target_url).await {
-            backlinks.extend(bing_links);
-        }
+        let visited_urls: Arc<Mutex<HashSet<String>>> = Arc::new(Mutex::new(HashSet::new()));
+        let mut urls_to_check = vec![target_url.to_string()];
 
-        Ok(backlinks)
-    }
+        while !urls_to_check.is_empty() {
+            if visited_urls.lock().await.len() >= self.max_pages {
+                break;
+            }
 
-    async fn search_google_backlinks(&self, target_url: &str) -> Result<Vec<BacklinkData>, CrawlError> {
-        let query = format!("link:{}", target_url);
-        let search_url = format!(
-            "https://www.google.com/search?q={}&num=100",
-            urlencoding::encode(&query)
-        );
+            let mut batch = Vec::new();
+            while batch.len() < self.max_concurrency {
+                let Some(url) = urls_to_check.pop() else { break };
 
-        let response = self.client.get(&search_url).send().await?;
-        let html = response.text().await?;
-        let document = Html::parse_document(&html);
+                let mut visited = visited_urls.lock().await;
+                if visited.contains(&url) || visited.len() >= self.max_pages || !self.is_domain_allowed(&url) {
+                    continue;
+                }
+                visited.insert(url.clone());
+                batch.push(url);
+            }
 
-        let link_selector = Selector::parse("a[href]").map_err(|_| CrawlError::ParseError("Invalid CSS selector".to_string()))?;
-        let mut backlinks = Vec::new();
+            if batch.is_empty() {
+                break;
+            }
 
-        for element in document.select(&link_selector) {
-            if let Some(href) = element.value().attr("href") {
-                if href.starts_with("http") && !href.contains("google.com") {
-                    let backlink = BacklinkData {
-                        source_url: href.to_string(),
-                        target_url: target_url.to_string(),
-                        anchor_text: element.text().collect::<String>(),
-                        context: String::new(),
-                        page_title: String::new(),
-                        domain_authority: 0.0,
-                        is_nofollow: false,
-                        crawl_date: chrono::Utc::now(),
-                    };
-                    backlinks.push(backlink);
+            let (tx, mut rx) = tokio::sync::mpsc::channel(batch.len());
+            for url in batch {
+                let processor = self.clone();
+                let tx = tx.clone();
+                let target_url = target_url.to_string();
+                tokio::spawn(async move {
+                    let _permit = processor.semaphore.acquire().await.expect("semaphore is never closed");
+                    if let Some(host) = Url::parse(&url).ok().and_then(|u| u.host_str().map(str::to_string)) {
+                        processor.rate_limiter.acquire(&host).await;
+                    }
+                    let result = processor.crawl_page_for_backlinks(&url, &target_url).await;
+                    let _ = tx.send(result).await;
+                });
+            }
+            drop(tx);
+
+            while let Some(result) = rx.recv().await {
+                let Ok(result) = result else { continue }; // skip pages that can't be crawled
+                for backlink in result.backlinks {
+                    if !backlinks.iter().any(|b| b.source_url == backlink.source_url) {
+                        backlinks.push(backlink);
+                    }
+                }
+                let visited = visited_urls.lock().await;
+                for link in result.outgoing_links {
+                    if !visited.contains(&link) {
+                        urls_to_check.push(link);
+                    }
                 }
             }
         }
@@ -114,34 +461,29 @@ impl BacklinkProcessor {
         Ok(backlinks)
     }
 
-    async fn search_bing_backlinks(&self, target_url: &str) -> Result<Vec<BacklinkData>, CrawlError> {
-        let query = format!("linkfromdomain:{}", target_url);
-        let search_url = format!(
-            "https://www.bing.com/search?q={}&count=50",
-            urlencoding::encode(&query)
-        );
-
-        let response = self.client.get(&search_url).send().await?;
-        let html = response.text().await?;
-        let document = Html::parse_document(&html);
-
-        let link_selector = Selector::parse("a[href]").map_err(|_| CrawlError::ParseError("Invalid CSS selector".to_string()))?;
+    /// Runs every registered `SearchEngine`, paginating each up to its own `max_pages`,
+    /// and merges whatever backlinks they find. One engine failing (e.g. a blocked
+    /// request) doesn't stop the others.
+    async fn get_search_engine_backlinks(&self, target_url: &str) -> Result<Vec<BacklinkData>, CrawlError> {
         let mut backlinks = Vec::new();
 
-        for element in document.select(&link_selector) {
-            if let Some(href) = element.value().attr("href") {
-                if href.starts_with("http") && !href.contains("bing.com") {
-                    let backlink = BacklinkData {
-                        source_url: href.to_string(),
-                        target_url: target_url.to_string(),
-                        anchor_text: element.text().collect::<String>(),
-                        context: String::new(),
-                        page_title: String::new(),
-                        domain_authority: 0.0,
-                        is_nofollow: false,
-                        crawl_date: chrono::Utc::now(),
-                    };
-                    backlinks.push(backlink);
+        for engine in &self.search_engines {
+            for page in 0..engine.max_pages() {
+                let search_url = engine.search_url(target_url, page);
+                let fetched = async {
+                    let response = self.client.get(&search_url).send().await?;
+                    let html = response.text().await?;
+                    engine.parse_results(&html, target_url)
+                }.await;
+
+                match fetched {
+                    Ok(page_backlinks) => {
+                        if page_backlinks.is_empty() {
+                            break; // no more results from this engine
+                        }
+                        backlinks.extend(page_backlinks);
+                    }
+                    Err(_) => break, // skip remaining pages for this engine
                 }
             }
         }
@@ -149,17 +491,38 @@ impl BacklinkProcessor {
         Ok(backlinks)
     }
 
-    async fn crawl_page_for_backlinks(&self, page_url: &str, target_url: &str) -> Result<Vec<BacklinkData>, CrawlError> {
+    /// Crawls `page_url` for links to `target_url`. Honors the page's combined
+    /// `<meta name="robots">`/`X-Robots-Tag` directives: a `noindex` page contributes no
+    /// backlinks at all, and a `nofollow` page's other links aren't returned for the
+    /// frontier to enqueue (though its link to `target_url`, if any, is still recorded).
+    async fn crawl_page_for_backlinks(&self, page_url: &str, target_url: &str) -> Result<PageCrawlResult, CrawlError> {
         let response = self.client.get(page_url).send().await?;
+        let x_robots_tag = response.headers()
+            .get("x-robots-tag")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
         let html = response.text().await?;
         let document = Html::parse_document(&html);
 
+        let meta_robots = self.extract_robots_meta(&document);
+        let directives = RobotsDirectives::parse(&format!("{}, {}", meta_robots, x_robots_tag));
+
+        if directives.noindex {
+            return Ok(PageCrawlResult { backlinks: vec![], outgoing_links: vec![] });
+        }
+
         let link_selector = Selector::parse("a[href]").map_err(|_| CrawlError::ParseError("Invalid CSS selector".to_string()))?;
         let mut backlinks = Vec::new();
+        let mut outgoing_links = Vec::new();
 
         for element in document.select(&link_selector) {
             if let Some(href) = element.value().attr("href") {
                 if let Ok(absolute_url) = self.resolve_url(page_url, href) {
+                    if !is_supported_scheme(&absolute_url) {
+                        continue;
+                    }
+
                     if absolute_url.as_str().contains(target_url) {
                         let anchor_text = element.text().collect::<String>();
                         let context = self.extract_link_context(&element, &document);
@@ -171,18 +534,21 @@ impl BacklinkProcessor {
                             context,
                             page_title: self.extract_page_title(&document),
                             domain_authority: 0.0,
-                            is_nofollow: element.value().attr("rel")
+                            is_nofollow: directives.nofollow || element.value().attr("rel")
                                 .map(|rel| rel.contains("nofollow"))
                                 .unwrap_or(false),
                             crawl_date: chrono::Utc::now(),
+                            threat_type: None,
                         };
                         backlinks.push(backlink);
+                    } else if !directives.nofollow && self.is_domain_allowed(absolute_url.as_str()) {
+                        outgoing_links.push(absolute_url.to_string());
                     }
                 }
             }
         }
 
-        Ok(backlinks)
+        Ok(PageCrawlResult { backlinks, outgoing_links })
     }
 
     fn resolve_url(&self, base_url: &str, href: &str) -> Result<Url, ParseError> {
@@ -221,13 +587,25 @@ impl BacklinkProcessor {
         }
     }
 
-    pub async fn calculate_domain_authority(&self, backlinks: &[BacklinkData]) -> std::collections::HashMap<String, f64> {
-        let mut domain_scores = std::collections::HashMap::new();
+    fn extract_robots_meta(&self, document: &Html) -> String {
+        let selector = Selector::parse("meta[name='robots']").unwrap();
+        document.select(&selector)
+            .next()
+            .and_then(|el| el.value().attr("content"))
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Scores each source domain by adding its own PageRank once per backlink it is the
+    /// source of (a weight of `1.0` per backlink for domains `pagerank_scores` has no
+    /// entry for), then scales the result to 0-100. Target domains get no score of their own.
+    pub async fn calculate_domain_authority(&self, backlinks: &[BacklinkData], pagerank_scores: &HashMap<String, f64>) -> HashMap<String, f64> {
+        let mut domain_scores: HashMap<String, f64> = HashMap::new();
 
         for backlink in backlinks {
             if let Ok(source_domain) = self.extract_domain(&backlink.source_url) {
-                let score = domain_scores.entry(source_domain).or_insert(0.0);
-                *score += 1.0; // Simple scoring based on backlink count
+                let weight = pagerank_scores.get(&source_domain).copied().unwrap_or(1.0);
+                *domain_scores.entry(source_domain).or_insert(0.0) += weight;
             }
         }
 
@@ -247,98 +625,214 @@ impl BacklinkProcessor {
         Ok(parsed.host_str().unwrap_or("").to_string())
     }
 
+    /// Flags backlinks whose source URL, anchor text and context score above
+    /// `SPAM_PROBABILITY_THRESHOLD` under the trained Bayesian classifier.
     pub fn detect_spam_backlinks(&self, backlinks: &[BacklinkData]) -> Vec<BacklinkData> {
-        let mut spam_backlinks = Vec::new();
+        backlinks.iter()
+            .filter(|backlink| {
+                let text = format!("{} {} {}", backlink.source_url, backlink.anchor_text, backlink.context);
+                self.spam_classifier.spam_probability(&text) >= SPAM_PROBABILITY_THRESHOLD
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Google Safe Browsing v4 `threatMatches:find` lookup: flags source domains already
+/// known to host malware, social engineering, or unwanted software.
+pub struct SafeBrowsingClient {
+    client: Client,
+    api_key: String,
+}
 
-        // Simple spam detection heuristics
-        let spam_indicators = [
-            "casino", "poker", "viagra", "pharmacy", "loan", "insurance",
-            "free-money", "make-money-fast", "weight-loss", "dating"
-        ];
+impl SafeBrowsingClient {
+    pub fn new(client: Client, api_key: String) -> Self {
+        Self { client, api_key }
+    }
 
-        for backlink in backlinks {
-            let is_spam = spam_indicators.iter().any(|indicator|
-                backlink.source_url.to_lowercase().contains(indicator) ||
-                backlink.anchor_text.to_lowercase().contains(indicator) ||
-                backlink.context.to_lowercase().contains(indicator)
-            );
-
-            if is_spam {
-                spam_backlinks.push(backlink.clone());
+    /// Looks up every entry in `urls` in one batched request, returning the matched
+    /// threat type (e.g. `"MALWARE"`) keyed by the URL that matched.
+    pub async fn check_urls(&self, urls: &[String]) -> Result<HashMap<String, String>, CrawlError> {
+        if urls.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let request_body = serde_json::json!({
+            "client": {
+                "clientId": "ratcrowler",
+                "clientVersion": "1.0.0",
+            },
+            "threatInfo": {
+                "threatTypes": ["MALWARE", "SOCIAL_ENGINEERING", "UNWANTED_SOFTWARE"],
+                "platformTypes": ["ANY_PLATFORM"],
+                "threatEntryTypes": ["URL"],
+                "threatEntries": urls.iter().map(|u| serde_json::json!({"url": u})).collect::<Vec<_>>(),
+            },
+        });
+
+        let response = self.client
+            .post("https://safebrowsing.googleapis.com/v4/threatMatches:find")
+            .query(&[("key", &self.api_key)])
+            .json(&request_body)
+            .send()
+            .await?;
+
+        let body: serde_json::Value = response.json().await?;
+        let mut threats = HashMap::new();
+        if let Some(matches) = body.get("matches").and_then(|m| m.as_array()) {
+            for m in matches {
+                let url = m.get("threat").and_then(|t| t.get("url")).and_then(|u| u.as_str());
+                let threat_type = m.get("threatType").and_then(|t| t.as_str());
+                if let (Some(url), Some(threat_type)) = (url, threat_type) {
+                    threats.insert(url.to_string(), threat_type.to_string());
+                }
             }
         }
 
-        spam_backlinks
+        Ok(threats)
     }
 }
 
 pub struct BacklinkAnalyzer {
     processor: BacklinkProcessor,
     database: BacklinkDatabase,
+    safe_browsing: Option<SafeBrowsingClient>,
 }
 
 impl BacklinkAnalyzer {
     pub fn new(processor: BacklinkProcessor, database: BacklinkDatabase) -> Self {
-        Self { processor, database }
+        Self { processor, database, safe_browsing: None }
+    }
+
+    /// Enables the Google Safe Browsing reputation check with the given API key.
+    pub fn with_safe_browsing(mut self, api_key: String) -> Self {
+        self.safe_browsing = Some(SafeBrowsingClient::new(Client::new(), api_key));
+        self
     }
 
     pub async fn analyze_backlinks(&mut self, target_url: &str) -> Result<BacklinkAnalysis, CrawlError> {
         // Discover backlinks
-        let backlinks = self.processor.discover_backlinks(target_url).await?;
+        let mut backlinks = self.processor.discover_backlinks(target_url).await?;
+
+        // Flag backlinks whose source domain Google Safe Browsing considers malicious
+        let malicious_backlinks = if let Some(safe_browsing) = &self.safe_browsing {
+            self.apply_safe_browsing(safe_browsing, &mut backlinks).await?
+        } else {
+            0
+        };
 
         // Store backlinks
         self.database.store_backlinks(&backlinks)?;
 
-        // Calculate domain authority
-        let domain_scores = self.processor.calculate_domain_authority(&backlinks).await;
+        // Calculate PageRank over the domain link graph, then weight domain authority by it
+        let pagerank_scores = self.calculate_pagerank(&backlinks, 0.85, 100, 1e-6);
+        self.database.store_pagerank_scores(&pagerank_scores)?;
+
+        let domain_scores = self.processor.calculate_domain_authority(&backlinks, &pagerank_scores).await;
         self.database.store_domain_scores(&domain_scores)?;
 
         // Detect spam backlinks
         let spam_backlinks = self.processor.detect_spam_backlinks(&backlinks);
 
-        // Calculate PageRank (simplified)
-        let pagerank_scores = self.calculate_pagerank(&backlinks);
-        self.database.store_pagerank_scores(&pagerank_scores)?;
-
+        let target_domain = self.processor.extract_domain(target_url).unwrap_or_default();
         Ok(BacklinkAnalysis {
             total_backlinks: backlinks.len(),
             unique_domains: domain_scores.len(),
             spam_backlinks: spam_backlinks.len(),
-            domain_authority: domain_scores.get(&self.processor.extract_domain(target_url).unwrap_or_default())
-                .copied().unwrap_or(0.0),
-            pagerank_score: pagerank_scores.get(target_url).copied().unwrap_or(0.0),
+            malicious_backlinks,
+            domain_authority: domain_scores.get(&target_domain).copied().unwrap_or(0.0),
+            pagerank_score: pagerank_scores.get(&target_domain).copied().unwrap_or(0.0),
         })
     }
 
-    fn calculate_pagerank(&self, backlinks: &[BacklinkData]) -> std::collections::HashMap<String, f64> {
-        // Simplified PageRank calculation
-        let mut scores = std::collections::HashMap::new();
-        let mut outgoing_links = std::collections::HashMap::new();
+    /// Looks up every unique source domain in `backlinks` against Safe Browsing and sets
+    /// `threat_type` on the backlinks whose domain matched. Returns the number flagged.
+    async fn apply_safe_browsing(&self, safe_browsing: &SafeBrowsingClient, backlinks: &mut [BacklinkData]) -> Result<usize, CrawlError> {
+        let unique_domains: Vec<String> = backlinks.iter()
+            .filter_map(|b| self.processor.extract_domain(&b.source_url).ok())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+
+        let threats = safe_browsing.check_urls(&unique_domains).await?;
+        if threats.is_empty() {
+            return Ok(0);
+        }
 
-        // Count outgoing links per domain
-        for backlink in backlinks {
+        let mut flagged = 0;
+        for backlink in backlinks.iter_mut() {
             if let Ok(domain) = self.processor.extract_domain(&backlink.source_url) {
-                *outgoing_links.entry(domain).or_insert(0) += 1;
+                if let Some(threat_type) = threats.get(&domain) {
+                    backlink.threat_type = Some(threat_type.clone());
+                    flagged += 1;
+                }
             }
         }
 
-        // Calculate PageRank scores
+        Ok(flagged)
+    }
+
+    /// Runs power-iteration PageRank over the directed domain graph implied by `backlinks`
+    /// (an edge from the source domain to the target domain for every backlink), mirroring
+    /// [`BacklinkDatabase::compute_pagerank`](crate::database::BacklinkDatabase::compute_pagerank)
+    /// but keyed by domain instead of URL. `damping` is the standard PageRank damping factor,
+    /// `max_iterations` bounds the iteration count, and `tolerance` is the L1 convergence
+    /// threshold between successive rank vectors.
+    fn calculate_pagerank(&self, backlinks: &[BacklinkData], damping: f64, max_iterations: usize, tolerance: f64) -> HashMap<String, f64> {
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+
         for backlink in backlinks {
-            if let Ok(domain) = self.processor.extract_domain(&backlink.source_url) {
-                let outgoing = *outgoing_links.get(&domain).unwrap_or(&1) as f64;
-                let score = scores.entry(backlink.target_url.clone()).or_insert(0.0);
-                *score += 1.0 / outgoing;
+            let (Ok(source), Ok(target)) = (
+                self.processor.extract_domain(&backlink.source_url),
+                self.processor.extract_domain(&backlink.target_url),
+            ) else {
+                continue;
+            };
+            if source == target {
+                continue;
             }
+            let next_id = index.len();
+            let source_id = *index.entry(source).or_insert(next_id);
+            let next_id = index.len();
+            let target_id = *index.entry(target).or_insert(next_id);
+            edges.push((source_id, target_id));
         }
 
-        // Normalize scores
-        let max_score = scores.values().cloned().fold(0.0, f64::max);
-        if max_score > 0.0 {
-            for score in scores.values_mut() {
-                *score = (*score / max_score) * 100.0;
+        let n = index.len();
+        if n == 0 {
+            return HashMap::new();
+        }
+
+        let mut out_degree = vec![0usize; n];
+        let mut out_links: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (source_id, target_id) in edges {
+            out_degree[source_id] += 1;
+            out_links[source_id].push(target_id);
+        }
+
+        let mut rank = vec![1.0 / n as f64; n];
+        for _ in 0..max_iterations {
+            let dangling_mass: f64 = (0..n).filter(|&i| out_degree[i] == 0).map(|i| rank[i]).sum();
+            let base = (1.0 - damping) / n as f64 + damping * dangling_mass / n as f64;
+            let mut next_rank = vec![base; n];
+            for source_id in 0..n {
+                if out_degree[source_id] == 0 {
+                    continue;
+                }
+                let share = damping * rank[source_id] / out_degree[source_id] as f64;
+                for &target_id in &out_links[source_id] {
+                    next_rank[target_id] += share;
+                }
+            }
+
+            let delta: f64 = rank.iter().zip(&next_rank).map(|(old, new)| (new - old).abs()).sum();
+            rank = next_rank;
+            if delta < tolerance {
+                break;
             }
         }
 
-        scores
+        index.into_iter().map(|(domain, id)| (domain, rank[id])).collect()
     }
 }
@@ -1,5 +1,5 @@
 use crate::models::{BacklinkData, CrawlerConfig};
-use crate::database::Database;
+use crate::database_new::Database;
 use anyhow::Result;
 use chrono::Utc;
 use futures::future::join_all;
@@ -7,41 +7,398 @@ use log::{info, warn, error, debug};
 use reqwest::{Client, ClientBuilder};
 use scraper::{Html, Selector};
 use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Semaphore;
 use tokio::time::sleep;
 use url::{Url, ParseError};
 use rand::seq::SliceRandom;
+use rand::Rng;
+use psl::Psl;
+use async_trait::async_trait;
+
+/// Per-token spamminess is clamped to this range so a token seen only on one side of the
+/// training set can never veto the whole combined score by itself.
+const MIN_TOKEN_SPAMMINESS: f64 = 0.01;
+const MAX_TOKEN_SPAMMINESS: f64 = 0.99;
+/// Number of most-informative tokens (furthest from the neutral `0.5`) combined per backlink.
+const MAX_INFORMATIVE_TOKENS: usize = 15;
+/// Default decision threshold for `(BacklinkData, f64)` scores; callers can compare against
+/// a different threshold if they want to be more or less aggressive.
+const DEFAULT_SPAM_THRESHOLD: f64 = 0.5;
 
 pub struct BacklinkProcessor {
-    client: Client,
+    /// One client per configured proxy (or a single direct client when none are
+    /// configured); requests pick one at random the same way they pick a user agent.
+    clients: Vec<Client>,
     database: Arc<Database>,
     config: CrawlerConfig,
     semaphore: Arc<Semaphore>,
     visited_urls: Arc<tokio::sync::Mutex<HashSet<String>>>,
     discovered_backlinks: Arc<tokio::sync::Mutex<Vec<BacklinkData>>>,
+    spam_threshold: f64,
+    robots_cache: Arc<tokio::sync::Mutex<HashMap<String, RobotsRules>>>,
+    rate_limiter: Arc<DomainRateLimiter>,
+}
+
+/// A host's parsed `robots.txt`, reduced to just what `BacklinkProcessor` needs: the
+/// disallowed path prefixes for our user agent and its crawl-delay, if any.
+#[derive(Clone, Default)]
+struct RobotsRules {
+    disallow: Vec<String>,
+    crawl_delay: Option<f64>,
+}
+
+impl RobotsRules {
+    /// Parses `robots.txt` content, keeping only the rules from the group whose
+    /// `User-agent` token is the longest case-insensitive prefix match of `user_agent`
+    /// (falling back to the `*` group).
+    fn parse(content: &str, user_agent: &str) -> Self {
+        let ua_lower = user_agent.to_ascii_lowercase();
+        let mut current_tokens: Vec<String> = Vec::new();
+        let mut best_match_len: Option<usize> = None;
+        let mut wildcard = RobotsRules::default();
+        let mut best = RobotsRules::default();
+        let mut in_best_group = false;
+        let mut in_wildcard_group = false;
+
+        for raw_line in content.lines() {
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+
+            match key.as_str() {
+                "user-agent" => {
+                    current_tokens = vec![value.to_ascii_lowercase()];
+                    in_wildcard_group = value == "*";
+                    in_best_group = ua_lower.starts_with(&current_tokens[0])
+                        && best_match_len.map_or(true, |best_len| current_tokens[0].len() > best_len);
+                    if in_best_group {
+                        best_match_len = Some(current_tokens[0].len());
+                        best = RobotsRules::default();
+                    }
+                }
+                "disallow" if !value.is_empty() => {
+                    if in_best_group {
+                        best.disallow.push(value.to_string());
+                    }
+                    if in_wildcard_group {
+                        wildcard.disallow.push(value.to_string());
+                    }
+                }
+                "crawl-delay" => {
+                    let delay = value.parse::<f64>().ok();
+                    if in_best_group {
+                        best.crawl_delay = delay;
+                    }
+                    if in_wildcard_group {
+                        wildcard.crawl_delay = delay;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if best_match_len.is_some() { best } else { wildcard }
+    }
+
+    fn is_disallowed(&self, path: &str) -> bool {
+        self.disallow.iter().any(|prefix| path.starts_with(prefix.as_str()))
+    }
+}
+
+/// `<meta name="robots" content="...">` (or equivalent `X-Robots-Tag`) directives.
+#[derive(Default)]
+struct RobotsMeta {
+    noindex: bool,
+    nofollow: bool,
+}
+
+impl RobotsMeta {
+    fn parse(content: &str) -> Self {
+        let lower = content.to_ascii_lowercase();
+        Self {
+            noindex: lower.contains("noindex"),
+            nofollow: lower.contains("nofollow"),
+        }
+    }
+}
+
+/// A throttled host's effective rate never drops below this fraction of its configured
+/// `requests_per_second_per_domain`, no matter how many 429/503s it has sent in a row.
+const MIN_RATE_MULTIPLIER: f64 = 0.05;
+/// Random extra wait (on top of whatever the bucket already requires) added to every
+/// `acquire`, so requests queued behind the same host's bucket don't all fire in lockstep.
+const RATE_LIMITER_JITTER_MS_MAX: u64 = 250;
+
+/// A single host's token bucket: `tokens` refill over time at `rate_multiplier *`
+/// the processor's configured rate, capped at `burst`.
+struct DomainBucket {
+    tokens: f64,
+    last_refill: Instant,
+    rate_multiplier: f64,
+    /// Set by a `Retry-After` response; `acquire` blocks until this passes regardless of
+    /// how many tokens are available.
+    not_before: Option<Instant>,
+}
+
+/// Per-host token-bucket rate limiter. Each host gets its own bucket so a slow or
+/// rate-limiting host throttles only itself, while the frontier as a whole keeps moving
+/// across every other domain at full concurrency.
+struct DomainRateLimiter {
+    buckets: tokio::sync::Mutex<HashMap<String, DomainBucket>>,
+    requests_per_second: f64,
+    burst: f64,
+}
+
+impl DomainRateLimiter {
+    fn new(requests_per_second: f64, burst: u32) -> Self {
+        Self {
+            buckets: tokio::sync::Mutex::new(HashMap::new()),
+            requests_per_second: requests_per_second.max(0.001),
+            burst: (burst.max(1)) as f64,
+        }
+    }
+
+    /// Blocks until `host` has a token available, treating `floor_ms` (e.g. a robots.txt
+    /// `Crawl-delay`) as a cap on the bucket's refill rate so requests never come in faster
+    /// than that regardless of `burst`.
+    async fn acquire(&self, host: &str, floor_ms: u64) {
+        let floor_rate = if floor_ms > 0 { 1000.0 / floor_ms as f64 } else { f64::INFINITY };
+        // With a floor in force the bucket holds at most one token, so no burst can beat it.
+        let capacity = if floor_ms > 0 { 1.0 } else { self.burst };
+
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().await;
+                let bucket = buckets.entry(host.to_string()).or_insert_with(|| DomainBucket {
+                    tokens: self.burst,
+                    last_refill: Instant::now(),
+                    rate_multiplier: 1.0,
+                    not_before: None,
+                });
+
+                let now = Instant::now();
+                if let Some(not_before) = bucket.not_before {
+                    if now < not_before {
+                        Some(not_before - now)
+                    } else {
+                        bucket.not_before = None;
+                        None
+                    }
+                } else {
+                    let rate = self.requests_per_second.min(floor_rate) * bucket.rate_multiplier;
+                    let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+                    bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+                    bucket.last_refill = now;
+
+                    if bucket.tokens >= 1.0 {
+                        bucket.tokens -= 1.0;
+                        None
+                    } else {
+                        Some(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+                    }
+                }
+            };
+
+            let Some(wait) = wait else { break };
+            let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..=RATE_LIMITER_JITTER_MS_MAX));
+            sleep(wait + jitter).await;
+        }
+    }
+
+    /// Halves `host`'s effective rate (down to `MIN_RATE_MULTIPLIER`) after it answers with
+    /// 429/503. If it sent a `Retry-After`, further requests to it are blocked until that
+    /// window elapses rather than just being rate-reduced.
+    async fn throttle(&self, host: &str, retry_after: Option<Duration>) {
+        let mut buckets = self.buckets.lock().await;
+        let bucket = buckets.entry(host.to_string()).or_insert_with(|| DomainBucket {
+            tokens: self.burst,
+            last_refill: Instant::now(),
+            rate_multiplier: 1.0,
+            not_before: None,
+        });
+        bucket.rate_multiplier = (bucket.rate_multiplier * 0.5).max(MIN_RATE_MULTIPLIER);
+        if let Some(retry_after) = retry_after {
+            bucket.not_before = Some(Instant::now() + retry_after);
+        }
+    }
+}
+
+/// Parses a `Retry-After` response header, which is either a number of seconds or an
+/// HTTP-date; we only need the relative form here.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+/// A pluggable way to discover backlinks to a target URL. `discover_backlinks` runs every
+/// registered source and merges their results, so a new discovery strategy can be added
+/// without touching its aggregation or dedup logic.
+#[async_trait]
+trait BacklinkSource {
+    async fn find_backlinks(&self, target: &str) -> Result<Vec<BacklinkData>>;
+}
+
+/// Crawls outward from `target` itself, following its own outbound links up to `max_depth`
+/// (the original backlink-discovery strategy).
+struct PageCrawlSource<'a> {
+    processor: &'a BacklinkProcessor,
+    max_depth: u32,
+}
+
+#[async_trait]
+impl<'a> BacklinkSource for PageCrawlSource<'a> {
+    async fn find_backlinks(&self, target: &str) -> Result<Vec<BacklinkData>> {
+        self.processor.discover_backlinks_for_url(target, self.max_depth).await
+    }
+}
+
+/// Scrapes Bing's `linkfromdomain:` search results for pages linking to `target`.
+struct BingSearchSource<'a> {
+    processor: &'a BacklinkProcessor,
+}
+
+#[async_trait]
+impl<'a> BacklinkSource for BingSearchSource<'a> {
+    async fn find_backlinks(&self, target: &str) -> Result<Vec<BacklinkData>> {
+        self.processor.search_bing_backlinks(target).await
+            .map_err(|e| anyhow::anyhow!(e.to_string()))
+    }
+}
+
+/// Queries the Common Crawl CDX index for pages likely to link to `target`.
+struct CommonCrawlSource<'a> {
+    processor: &'a BacklinkProcessor,
+}
+
+#[async_trait]
+impl<'a> BacklinkSource for CommonCrawlSource<'a> {
+    async fn find_backlinks(&self, target: &str) -> Result<Vec<BacklinkData>> {
+        self.processor.search_common_crawl_backlinks(target).await
+    }
 }
 
 impl BacklinkProcessor {
     pub fn new(database: Arc<Database>, config: CrawlerConfig) -> Result<Self> {
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(config.request_timeout_seconds))
-            .user_agent(&config.user_agents[0])
-            .gzip(true)
-            .brotli(true)
-            .build()?;
+        let clients = Self::build_clients(&config)?;
 
         Ok(Self {
-            client,
+            clients,
             database,
             semaphore: Arc::new(Semaphore::new(config.max_concurrent_requests)),
+            rate_limiter: Arc::new(DomainRateLimiter::new(config.requests_per_second_per_domain, config.burst)),
             config,
             visited_urls: Arc::new(tokio::sync::Mutex::new(HashSet::new())),
             discovered_backlinks: Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            spam_threshold: DEFAULT_SPAM_THRESHOLD,
+            robots_cache: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
         })
     }
 
+    /// Builds one `Client` per entry in `config.proxies` (each routed through its proxy via
+    /// `ClientBuilder::proxy`), or a single direct client when no proxies are configured, so
+    /// a blocked or rate-limited proxy doesn't take the whole crawl down with it.
+    fn build_clients(config: &CrawlerConfig) -> Result<Vec<Client>> {
+        let proxy_urls: Vec<Option<&str>> = if config.proxies.is_empty() {
+            vec![None]
+        } else {
+            config.proxies.iter().map(|url| Some(url.as_str())).collect()
+        };
+
+        proxy_urls
+            .into_iter()
+            .map(|proxy_url| {
+                let mut builder = ClientBuilder::new()
+                    .timeout(Duration::from_secs(config.request_timeout_seconds))
+                    .user_agent(&config.user_agents[0])
+                    .gzip(true)
+                    .brotli(true);
+                if let Some(proxy_url) = proxy_url {
+                    builder = builder.proxy(reqwest::Proxy::all(proxy_url)?);
+                }
+                Ok(builder.build()?)
+            })
+            .collect()
+    }
+
+    /// Picks one of the configured clients at random, rotating across proxies the same way
+    /// `extract_backlinks_from_page` rotates user agents.
+    fn pick_client(&self) -> &Client {
+        self.clients.choose(&mut rand::thread_rng()).unwrap_or(&self.clients[0])
+    }
+
+    /// Fetches (or returns the cached) `robots.txt` rules for `url`'s host and reports
+    /// whether our configured user agent may crawl `url`. Always returns `true` when
+    /// `config.respect_robots_txt` is off.
+    async fn is_allowed_by_robots(&self, url: &str) -> Result<bool> {
+        if !self.config.respect_robots_txt {
+            return Ok(true);
+        }
+
+        let parsed = Url::parse(url)?;
+        let user_agent = self.config.user_agents.first().map(String::as_str).unwrap_or("*");
+        let robots_url = format!("{}/robots.txt", parsed.origin().unicode_serialization());
+
+        {
+            let cache = self.robots_cache.lock().await;
+            if let Some(rules) = cache.get(&robots_url) {
+                return Ok(!rules.is_disallowed(parsed.path()));
+            }
+        }
+
+        let rules = match self.pick_client().get(&robots_url).send().await {
+            Ok(response) if response.status().is_success() => {
+                let body = response.text().await.unwrap_or_default();
+                RobotsRules::parse(&body, user_agent)
+            }
+            _ => RobotsRules::default(),
+        };
+        let allowed = !rules.is_disallowed(parsed.path());
+        self.robots_cache.lock().await.insert(robots_url, rules);
+        Ok(allowed)
+    }
+
+    /// Returns `url`'s registrable domain (eTLD+1, e.g. `www.example.co.uk` -> `example.co.uk`)
+    /// using the public suffix list, so subdomains of the same site aren't mistaken for
+    /// external backlinks and multi-label suffixes like `.co.uk` are handled correctly.
+    fn reduced_domain(&self, url: &str) -> Result<String> {
+        let host = Url::parse(url)?.host_str().unwrap_or("").to_string();
+        let domain = psl::List.domain(host.as_bytes())
+            .map(|d| String::from_utf8_lossy(d.as_bytes()).to_string())
+            .unwrap_or(host);
+        Ok(domain)
+    }
+
+    /// True when `url`'s registrable domain is not on the weed list, and is on the allow
+    /// list whenever that list is non-empty.
+    fn is_domain_allowed(&self, url: &str) -> bool {
+        let Ok(domain) = self.reduced_domain(url) else { return false };
+        if self.config.weed_domains.iter().any(|d| d == &domain) {
+            return false;
+        }
+        self.config.allowed_domains.is_empty()
+            || self.config.allowed_domains.iter().any(|d| d == &domain)
+    }
+
+    /// Parses the page's `<meta name="robots">` tag, if any.
+    fn extract_robots_meta(&self, document: &Html) -> RobotsMeta {
+        let selector = Selector::parse("meta[name='robots']").unwrap();
+        document.select(&selector)
+            .next()
+            .and_then(|el| el.value().attr("content"))
+            .map(RobotsMeta::parse)
+            .unwrap_or_default()
+    }
+
+    /// Overrides the decision threshold `detect_spam_backlinks` uses to turn a combined
+    /// spam probability into a yes/no filter.
+    pub fn with_spam_threshold(mut self, threshold: f64) -> Self {
+        self.spam_threshold = threshold;
+        self
+    }
+
     pub async fn process_backlinks_for_duration(&self, duration_hours: u64) -> Result<usize> {
         info!("Starting backlink processing for {} hours", duration_hours);
         let start_time = Instant::now();
@@ -105,6 +462,71 @@ impl BacklinkProcessor {
         Ok(total_backlinks)
     }
 
+    /// Runs every registered `BacklinkSource` against `target_url` and merges their results,
+    /// deduping by `(source_url, target_url)` so the same backlink found by e.g. both the
+    /// page crawler and a search engine isn't stored twice. A source erroring out (a search
+    /// engine blocking us, the CDX API being down) is logged and skipped rather than failing
+    /// the whole discovery run.
+    pub async fn discover_backlinks(&self, target_url: &str) -> Result<Vec<BacklinkData>> {
+        let sources: Vec<Box<dyn BacklinkSource + '_>> = vec![
+            Box::new(PageCrawlSource { processor: self, max_depth: 3 }),
+            Box::new(BingSearchSource { processor: self }),
+            Box::new(CommonCrawlSource { processor: self }),
+        ];
+
+        let mut seen = HashSet::new();
+        let mut backlinks = Vec::new();
+        for source in &sources {
+            match source.find_backlinks(target_url).await {
+                Ok(found) => {
+                    for backlink in found {
+                        if seen.insert((backlink.source_url.clone(), backlink.target_url.clone())) {
+                            backlinks.push(backlink);
+                        }
+                    }
+                }
+                Err(e) => warn!("Backlink source failed for {}: {}", target_url, e),
+            }
+        }
+
+        Ok(backlinks)
+    }
+
+    /// Queries the Common Crawl CDX index for captures whose URL already references
+    /// `target_url`'s domain (syndication/referral links tend to carry it in the path or
+    /// query string), then confirms each candidate page actually links out to it via the
+    /// same extraction `extract_backlinks_from_page` uses for the live crawl. The CDX API
+    /// indexes captured pages, not an inbound-link graph, so this is a best-effort filter
+    /// rather than an exhaustive one.
+    async fn search_common_crawl_backlinks(&self, target_url: &str) -> Result<Vec<BacklinkData>> {
+        let domain = self.reduced_domain(target_url)?;
+        let cdx_url = format!(
+            "https://index.commoncrawl.org/CC-MAIN-2024-33-index?url=*{}*&output=json&limit=50",
+            domain
+        );
+
+        let response = self.pick_client().get(&cdx_url).send().await?;
+        if !response.status().is_success() {
+            return Ok(Vec::new());
+        }
+        let body = response.text().await?;
+
+        let mut backlinks = Vec::new();
+        for line in body.lines() {
+            let Ok(record) = serde_json::from_str::<serde_json::Value>(line) else { continue };
+            let Some(page_url) = record.get("url").and_then(|v| v.as_str()) else { continue };
+
+            if let Ok((found, _)) = self.extract_backlinks_from_page(page_url).await {
+                backlinks.extend(
+                    found.into_iter()
+                        .filter(|b| self.reduced_domain(&b.target_url).map_or(false, |d| d == domain)),
+                );
+            }
+        }
+
+        Ok(backlinks)
+    }
+
     async fn discover_backlinks_for_url(&self, url: &str, max_depth: u32) -> Result<Vec<BacklinkData>> {
         let mut discovered = Vec::new();
         let mut queue = vec![(url.to_string(), 0)];
@@ -116,17 +538,28 @@ impl BacklinkProcessor {
             }
 
             visited.insert(current_url.clone());
-            
+
+            if !self.is_allowed_by_robots(&current_url).await.unwrap_or(true) {
+                debug!("Skipping {} (disallowed by robots.txt)", current_url);
+                continue;
+            }
+
             // Get permit for concurrent request
             let _permit = self.semaphore.acquire().await?;
-            
+
+            // Wait for this host's token bucket, never exceeding its robots.txt Crawl-delay
+            // (if any) and backed further off by any recent 429/503s.
+            let host = Url::parse(&current_url).ok().and_then(|u| u.host_str().map(str::to_string)).unwrap_or_default();
+            let floor_ms = self.crawl_delay_floor_ms(&current_url).await;
+            self.rate_limiter.acquire(&host, floor_ms).await;
+
             match self.extract_backlinks_from_page(&current_url).await {
                 Ok((backlinks, outbound_links)) => {
                     discovered.extend(backlinks);
                     
                     // Add outbound links to queue for further processing
                     for link in outbound_links.into_iter().take(5) { // Limit to 5 per page
-                        if !visited.contains(&link) {
+                        if !visited.contains(&link) && self.is_domain_allowed(&link) {
                             queue.push((link, depth + 1));
                         }
                     }
@@ -135,20 +568,28 @@ impl BacklinkProcessor {
                     debug!("Failed to extract backlinks from {}: {}", current_url, e);
                 }
             }
-
-            // Respect delay
-            sleep(Duration::from_millis(self.config.delay_between_requests_ms)).await;
         }
 
         Ok(discovered)
     }
 
+    /// The `Crawl-delay` (in ms) from `url`'s host's cached `robots.txt`, if any, else `0`.
+    async fn crawl_delay_floor_ms(&self, url: &str) -> u64 {
+        let Ok(parsed) = Url::parse(url) else { return 0 };
+        let robots_url = format!("{}/robots.txt", parsed.origin().unicode_serialization());
+        let cache = self.robots_cache.lock().await;
+        cache.get(&robots_url)
+            .and_then(|rules| rules.crawl_delay)
+            .map(|secs| (secs * 1000.0) as u64)
+            .unwrap_or(0)
+    }
+
     async fn extract_backlinks_from_page(&self, url: &str) -> Result<(Vec<BacklinkData>, Vec<String>)> {
         let user_agent = self.config.user_agents
             .choose(&mut rand::thread_rng())
             .unwrap_or(&self.config.user_agents[0]);
 
-        let response = self.client
+        let response = self.pick_client()
             .get(url)
             .header("User-Agent", user_agent)
             .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
@@ -156,16 +597,29 @@ impl BacklinkProcessor {
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("HTTP error: {}", response.status()));
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+            if let Some(host) = Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string)) {
+                let retry_after = response.headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(parse_retry_after);
+                self.rate_limiter.throttle(&host, retry_after).await;
+            }
+        }
+
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("HTTP error: {}", status));
         }
 
         let body = response.text().await?;
         let document = Html::parse_document(&body);
-        
+
         let mut backlinks = Vec::new();
         let mut outbound_links = Vec::new();
-        
+
+        let robots_meta = self.extract_robots_meta(&document);
+
         // Extract page title
         let title_selector = Selector::parse("title").unwrap();
         let page_title = document
@@ -176,23 +630,28 @@ impl BacklinkProcessor {
 
         // Extract all links
         let link_selector = Selector::parse("a[href]").unwrap();
-        
+
         for element in document.select(&link_selector) {
             if let Some(href) = element.value().attr("href") {
                 match Url::parse(href).or_else(|_| Url::parse(url)?.join(href)) {
                     Ok(link_url) => {
                         let link_str = link_url.to_string();
-                        
-                        // Check if it's an external link (potential backlink)
-                        let source_domain = Url::parse(url)?.host_str().unwrap_or("");
-                        let target_domain = link_url.host_str().unwrap_or("");
-                        
-                        if source_domain != target_domain && !target_domain.is_empty() {
+                        let is_nofollow = element.value().attr("rel")
+                            .map(|rel| rel.contains("nofollow"))
+                            .unwrap_or(false);
+
+                        if !self.is_domain_allowed(&link_str) {
+                            continue;
+                        }
+
+                        // Check if it's an external link (potential backlink), comparing
+                        // registrable domains so subdomains of the same site don't count
+                        let source_domain = self.reduced_domain(url).unwrap_or_default();
+                        let target_domain = self.reduced_domain(&link_str).unwrap_or_default();
+
+                        if !robots_meta.noindex && source_domain != target_domain && !target_domain.is_empty() {
                             let anchor_text = element.text().collect::<String>();
                             let context = self.extract_context(&element, &document);
-                            let is_nofollow = element.value().attr("rel")
-                                .map(|rel| rel.contains("nofollow"))
-                                .unwrap_or(false);
 
                             let backlink = BacklinkData {
                                 source_url: url.to_string(),
@@ -204,12 +663,15 @@ impl BacklinkProcessor {
                                 is_nofollow,
                                 discovered_at: Utc::now(),
                             };
-                            
+
                             backlinks.push(backlink);
                         }
-                        
-                        // Add to outbound links for further crawling
-                        outbound_links.push(link_str);
+
+                        // Add to outbound links for further crawling, unless the page or
+                        // the link itself is marked nofollow
+                        if !robots_meta.nofollow && !is_nofollow {
+                            outbound_links.push(link_str);
+                        }
                     }
                     Err(_) => {
                         debug!("Failed to parse URL: {}", href);
@@ -264,17 +726,117 @@ impl BacklinkProcessor {
         let backlinks_count = self.discovered_backlinks.lock().await.len();
         Ok((visited_count, backlinks_count))
     }
+
+    /// Lowercased word tokens drawn from a backlink's anchor text, surrounding context, and
+    /// its source URL's host+path (the parts of a backlink most obfuscated spam tries to hide
+    /// in, since query strings and fragments are noisy and rarely carry signal).
+    fn tokenize_backlink(&self, backlink: &BacklinkData) -> Vec<String> {
+        let mut text = format!("{} {}", backlink.anchor_text, backlink.context);
+        if let Ok(source) = Url::parse(&backlink.source_url) {
+            text.push(' ');
+            text.push_str(source.host_str().unwrap_or(""));
+            text.push(' ');
+            text.push_str(source.path());
+        }
+
+        text.split(|c: char| !c.is_alphanumeric())
+            .map(|word| word.to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect()
+    }
+
+    /// Splits a token's 64-bit hash into two i32 halves for a SQLite-friendly composite key.
+    fn token_hash_halves(token: &str) -> (i32, i32) {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        token.hash(&mut hasher);
+        let hash = hasher.finish();
+        ((hash >> 32) as i32, hash as i32)
+    }
+
+    /// Trains the spam classifier on a labeled backlink, incrementing `spam_count` or
+    /// `ham_count` for each of its tokens in the database's token table.
+    pub async fn train_backlink(&self, backlink: &BacklinkData, is_spam: bool) -> Result<()> {
+        for token in self.tokenize_backlink(backlink) {
+            let (h1, h2) = Self::token_hash_halves(&token);
+            self.database.record_spam_token(h1, h2, is_spam).await?;
+        }
+        Ok(())
+    }
+
+    /// Scores a backlink's spam probability using Robinson's/Fisher's method: each token's
+    /// spamminess `p_t = (spam_count/total_spam) / (spam_count/total_spam + ham_count/total_ham)`
+    /// is clamped to `[0.01, 0.99]`, the most informative tokens (furthest from neutral `0.5`)
+    /// are combined as `1 / (1 + exp(sum(ln(1-p_t)) - sum(ln(p_t))))`, and the result is paired
+    /// with the backlink for the caller to store.
+    pub async fn score_backlink_spam(&self, backlink: &BacklinkData) -> Result<(BacklinkData, f64)> {
+        let tokens = self.tokenize_backlink(backlink);
+        let hashes: Vec<(i32, i32)> = tokens.iter().map(|t| Self::token_hash_halves(t)).collect();
+
+        let (total_spam, total_ham) = self.database.spam_token_totals().await?;
+        if total_spam == 0 || total_ham == 0 || hashes.is_empty() {
+            return Ok((backlink.clone(), 0.5));
+        }
+
+        let counts = self.database.spam_token_counts(&hashes).await?;
+        let mut spamminess: Vec<f64> = hashes
+            .into_iter()
+            .filter_map(|hash| counts.get(&hash))
+            .map(|&(spam_count, ham_count)| {
+                let spam_rate = spam_count as f64 / total_spam as f64;
+                let ham_rate = ham_count as f64 / total_ham as f64;
+                let p = if spam_rate + ham_rate > 0.0 {
+                    spam_rate / (spam_rate + ham_rate)
+                } else {
+                    0.5
+                };
+                p.clamp(MIN_TOKEN_SPAMMINESS, MAX_TOKEN_SPAMMINESS)
+            })
+            .collect();
+
+        spamminess.sort_by(|a, b| {
+            let a_weight = (a - 0.5).abs();
+            let b_weight = (b - 0.5).abs();
+            b_weight.partial_cmp(&a_weight).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        spamminess.truncate(MAX_INFORMATIVE_TOKENS);
+
+        if spamminess.is_empty() {
+            return Ok((backlink.clone(), 0.5));
+        }
+
+        let sum_ln_p: f64 = spamminess.iter().map(|p| p.ln()).sum();
+        let sum_ln_not_p: f64 = spamminess.iter().map(|p| (1.0 - p).ln()).sum();
+        let probability = 1.0 / (1.0 + (sum_ln_not_p - sum_ln_p).exp());
+
+        Ok((backlink.clone(), probability))
+    }
+
+    /// Scores every backlink and returns those at or above `spam_threshold`, replacing the
+    /// fixed keyword-list heuristic with the trainable Bayesian classifier.
+    pub async fn detect_spam_backlinks(&self, backlinks: &[BacklinkData]) -> Result<Vec<BacklinkData>> {
+        let mut spam_backlinks = Vec::new();
+        for backlink in backlinks {
+            let (backlink, probability) = self.score_backlink_spam(backlink).await?;
+            if probability >= self.spam_threshold {
+                spam_backlinks.push(backlink);
+            }
+        }
+        Ok(spam_backlinks)
+    }
 }
 
 impl Clone for BacklinkProcessor {
     fn clone(&self) -> Self {
         Self {
-            client: self.client.clone(),
+            clients: self.clients.clone(),
+            rate_limiter: self.rate_limiter.clone(),
             database: self.database.clone(),
             config: self.config.clone(),
             semaphore: self.semaphore.clone(),
             visited_urls: self.visited_urls.clone(),
             discovered_backlinks: self.discovered_backlinks.clone(),
+            spam_threshold: self.spam_threshold,
+            robots_cache: self.robots_cache.clone(),
         }
     }
 }
@@ -286,7 +848,7 @@ impl Clone for BacklinkProcessor {
             urlencoding::encode(&query)
         );
 
-        let response = self.client.get(&search_url).send().await?;
+        let response = self.pick_client().get(&search_url).send().await?;
         let html = response.text().await?;
         let document = Html::parse_document(&html);
 
@@ -315,7 +877,7 @@ impl Clone for BacklinkProcessor {
     }
 
     async fn crawl_page_for_backlinks(&self, page_url: &str, target_url: &str) -> Result<Vec<BacklinkData>, CrawlError> {
-        let response = self.client.get(page_url).send().await?;
+        let response = self.pick_client().get(page_url).send().await?;
         let html = response.text().await?;
         let document = Html::parse_document(&html);
 
@@ -390,7 +952,7 @@ impl Clone for BacklinkProcessor {
         let mut domain_scores = std::collections::HashMap::new();
 
         for backlink in backlinks {
-            if let Ok(source_domain) = self.extract_domain(&backlink.source_url) {
+            if let Ok(source_domain) = self.reduced_domain(&backlink.source_url) {
                 let score = domain_scores.entry(source_domain).or_insert(0.0);
                 *score += 1.0; // Simple scoring based on backlink count
             }
@@ -407,11 +969,6 @@ impl Clone for BacklinkProcessor {
         domain_scores
     }
 
-    fn extract_domain(&self, url: &str) -> Result<String, ParseError> {
-        let parsed = Url::parse(url)?;
-        Ok(parsed.host_str().unwrap_or("").to_string())
-    }
-
     pub fn detect_spam_backlinks(&self, backlinks: &[BacklinkData]) -> Vec<BacklinkData> {
         let mut spam_backlinks = Vec::new();
 
@@ -461,49 +1018,82 @@ impl BacklinkAnalyzer {
         // Detect spam backlinks
         let spam_backlinks = self.processor.detect_spam_backlinks(&backlinks);
 
-        // Calculate PageRank (simplified)
-        let pagerank_scores = self.calculate_pagerank(&backlinks);
+        // Calculate PageRank
+        let pagerank_scores = self.calculate_pagerank(&backlinks, 0.85, 100, 1e-6);
         self.database.store_pagerank_scores(&pagerank_scores)?;
 
         Ok(BacklinkAnalysis {
             total_backlinks: backlinks.len(),
             unique_domains: domain_scores.len(),
             spam_backlinks: spam_backlinks.len(),
-            domain_authority: domain_scores.get(&self.processor.extract_domain(target_url).unwrap_or_default())
+            malicious_backlinks: 0,
+            domain_authority: domain_scores.get(&self.processor.reduced_domain(target_url).unwrap_or_default())
                 .copied().unwrap_or(0.0),
             pagerank_score: pagerank_scores.get(target_url).copied().unwrap_or(0.0),
         })
     }
 
-    fn calculate_pagerank(&self, backlinks: &[BacklinkData]) -> std::collections::HashMap<String, f64> {
-        // Simplified PageRank calculation
-        let mut scores = std::collections::HashMap::new();
-        let mut outgoing_links = std::collections::HashMap::new();
+    /// Runs power-iteration PageRank over the directed graph of distinct URLs implied by
+    /// `backlinks` (an edge from `source_url` to `target_url` for every row). `damping` is the
+    /// standard PageRank damping factor, `max_iterations` bounds the iteration count, and
+    /// `epsilon` is the L1 convergence threshold between successive rank vectors. The final
+    /// scores are normalized to 0-100.
+    fn calculate_pagerank(&self, backlinks: &[BacklinkData], damping: f64, max_iterations: usize, epsilon: f64) -> std::collections::HashMap<String, f64> {
+        let mut index: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+        let mut edges: Vec<(usize, usize)> = Vec::new();
 
-        // Count outgoing links per domain
         for backlink in backlinks {
-            if let Ok(domain) = self.processor.extract_domain(&backlink.source_url) {
-                *outgoing_links.entry(domain).or_insert(0) += 1;
+            if backlink.source_url == backlink.target_url {
+                continue;
             }
+            let next_id = index.len();
+            let source_id = *index.entry(backlink.source_url.clone()).or_insert(next_id);
+            let next_id = index.len();
+            let target_id = *index.entry(backlink.target_url.clone()).or_insert(next_id);
+            edges.push((source_id, target_id));
         }
 
-        // Calculate PageRank scores
-        for backlink in backlinks {
-            if let Ok(domain) = self.processor.extract_domain(&backlink.source_url) {
-                let outgoing = *outgoing_links.get(&domain).unwrap_or(&1) as f64;
-                let score = scores.entry(backlink.target_url.clone()).or_insert(0.0);
-                *score += 1.0 / outgoing;
+        let n = index.len();
+        if n == 0 {
+            return std::collections::HashMap::new();
+        }
+
+        let mut out_degree = vec![0usize; n];
+        let mut out_links: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (source_id, target_id) in edges {
+            out_degree[source_id] += 1;
+            out_links[source_id].push(target_id);
+        }
+
+        let mut rank = vec![1.0 / n as f64; n];
+        for _ in 0..max_iterations {
+            let dangling_mass: f64 = (0..n).filter(|&i| out_degree[i] == 0).map(|i| rank[i]).sum();
+            let base = (1.0 - damping) / n as f64 + damping * dangling_mass / n as f64;
+            let mut next_rank = vec![base; n];
+            for source_id in 0..n {
+                if out_degree[source_id] == 0 {
+                    continue;
+                }
+                let share = damping * rank[source_id] / out_degree[source_id] as f64;
+                for &target_id in &out_links[source_id] {
+                    next_rank[target_id] += share;
+                }
+            }
+
+            let delta: f64 = rank.iter().zip(&next_rank).map(|(old, new)| (new - old).abs()).sum();
+            rank = next_rank;
+            if delta < epsilon {
+                break;
             }
         }
 
-        // Normalize scores
-        let max_score = scores.values().cloned().fold(0.0, f64::max);
+        let max_score = rank.iter().cloned().fold(0.0, f64::max);
         if max_score > 0.0 {
-            for score in scores.values_mut() {
+            for score in rank.iter_mut() {
                 *score = (*score / max_score) * 100.0;
             }
         }
 
-        scores
+        index.into_iter().map(|(url, id)| (url, rank[id])).collect()
     }
 }
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+/// Where the on-disk URL cache lives and how long an entry stays usable before it's treated
+/// as stale and the URL is re-fetched unconditionally.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    pub directory: String,
+    pub ttl_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            directory: "cache/pages".to_string(),
+            ttl_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+/// What the cache remembers about the last successful fetch of a URL: enough to send a
+/// conditional request next time and to notice a byte-identical re-fetch even when the
+/// server doesn't support `ETag`/`Last-Modified`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub content_hash: String,
+    pub etag: String,
+    pub last_modified: String,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// On-disk cache keyed by URL, one bincode-encoded file per entry named after the URL's
+/// SHA-256 hash. Lets `run_web_crawling` skip re-processing a page it just fetched within
+/// `CacheConfig::ttl_secs`, instead of re-running extraction/storage every scheduled pass.
+pub struct UrlCache {
+    directory: PathBuf,
+    ttl_secs: u64,
+}
+
+impl UrlCache {
+    pub fn new(config: &CacheConfig) -> Result<Self> {
+        let directory = PathBuf::from(&config.directory);
+        std::fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            ttl_secs: config.ttl_secs,
+        })
+    }
+
+    fn path_for(&self, url: &str) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(url.as_bytes());
+        self.directory.join(format!("{:x}.bin", hasher.finalize()))
+    }
+
+    /// Returns the cached entry for `url`, or `None` if there isn't one, it's corrupt, or
+    /// it's older than `ttl_secs`.
+    pub fn get(&self, url: &str) -> Option<CacheEntry> {
+        let bytes = std::fs::read(self.path_for(url)).ok()?;
+        let entry: CacheEntry = bincode::deserialize(&bytes).ok()?;
+        let age = Utc::now().signed_duration_since(entry.fetched_at);
+        if age.num_seconds() > self.ttl_secs as i64 {
+            return None;
+        }
+        Some(entry)
+    }
+
+    pub fn put(&self, url: &str, entry: &CacheEntry) -> Result<()> {
+        let bytes = bincode::serialize(entry)?;
+        std::fs::write(self.path_for(url), bytes)?;
+        Ok(())
+    }
+}
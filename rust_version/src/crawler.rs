@@ -1,25 +1,40 @@
 use reqwest::Client;
 use scraper::{Html, Selector};
 use std::collections::{HashSet, BinaryHeap, HashMap};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::cmp::Ordering;
 use url::{Url, ParseError};
 use regex::Regex;
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use futures::StreamExt;
+use log::debug;
+use tokio::sync::{Mutex, Semaphore};
 use crate::models::*;
 use crate::database::WebsiteCrawlerDatabase;
 
+const MAX_SITEMAP_RECURSION_DEPTH: usize = 3;
+const MAX_SITEMAPS_FOLLOWED: usize = 50;
+// Below this many characters of extracted text, the n-gram classifier's confidence is too
+// low to be worth trusting, so we record "unknown" rather than guess.
+const MIN_LANGUAGE_DETECTION_CHARS: usize = 50;
+
 #[derive(Eq, PartialEq)]
 struct UrlPriority {
     url: String,
     priority: i32,
     depth: usize,
+    // Sitemap `<lastmod>`, if known; used to prefer recently-updated pages on a tie.
+    lastmod: Option<DateTime<Utc>>,
 }
 
 impl Ord for UrlPriority {
     fn cmp(&self, other: &Self) -> Ordering {
-        // Higher priority first, then lower depth
+        // Higher priority first, then lower depth, then more recently modified
         other.priority.cmp(&self.priority)
             .then_with(|| self.depth.cmp(&other.depth))
+            .then_with(|| self.lastmod.cmp(&other.lastmod))
     }
 }
 
@@ -29,6 +44,24 @@ impl PartialOrd for UrlPriority {
     }
 }
 
+/// Result of following a URL's redirect chain to completion: either the response was a
+/// conditional-GET `304 Not Modified` (caller should reuse the cached page), or a fresh
+/// body was downloaded.
+enum FetchOutcome {
+    NotModified(String),
+    Fetched {
+        final_url: String,
+        status_code: u16,
+        redirect_chain: Vec<String>,
+        charset: String,
+        content_type: String,
+        x_robots_tag: String,
+        etag: String,
+        last_modified: String,
+        body: Vec<u8>,
+    },
+}
+
 pub struct WebsiteCrawler {
     client: Client,
     user_agent: String,
@@ -36,9 +69,35 @@ pub struct WebsiteCrawler {
     max_redirects: usize,
     max_depth: usize,
     max_pages: usize,
+    max_concurrency: usize,
     delay_ms: u64,
     respect_robots_txt: bool,
-    robots_cache: HashMap<String, RobotsTxt>,
+    max_body_bytes: usize,
+    fetch_timeout: Duration,
+    robots_cache: Arc<Mutex<HashMap<String, RobotsTxt>>>,
+    // Per-host "earliest next request" timestamps, so concurrent workers still space out
+    // requests to the same origin instead of just the frontier's global `delay_ms`.
+    host_gate: Arc<Mutex<HashMap<String, Instant>>>,
+    semaphore: Arc<Semaphore>,
+    /// When non-empty, only hosts matching one of these (or a subdomain of one) are
+    /// crawled; everything else is skipped before it enters the frontier.
+    allowed_domains: HashSet<String>,
+    /// Hosts (or their subdomains) that are never crawled, regardless of `allowed_domains`.
+    weed_domains: HashSet<String>,
+    respect_meta_robots: bool,
+    /// Additional hard cap on pages fetched this crawl, enforced alongside `max_pages`.
+    page_budget: Option<usize>,
+    /// Cap on how many links are taken from a single page's extracted link list.
+    links_per_page_budget: Option<usize>,
+    /// Additional depth cap enforced alongside `max_depth`.
+    max_level: Option<usize>,
+    /// Base `Content-Type` values a page body must have to be downloaded.
+    accepted_content_types: Vec<String>,
+}
+
+/// True when `host` is (or is a subdomain of) `domain`.
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
 }
 
 impl WebsiteCrawler {
@@ -46,7 +105,9 @@ impl WebsiteCrawler {
         let client = Client::builder()
             .user_agent(&config.user_agent)
             .timeout(Duration::from_secs(config.timeout_secs))
-            .redirect(reqwest::redirect::Policy::limited(config.max_redirects))
+            // Redirects are followed manually in `crawl_single_page` so we can record each
+            // hop into `redirect_chain` and detect loops instead of losing that history.
+            .redirect(reqwest::redirect::Policy::none())
             .build()
             .expect("Failed to build HTTP client");
 
@@ -57,13 +118,36 @@ impl WebsiteCrawler {
             max_redirects: config.max_redirects,
             max_depth: config.max_depth,
             max_pages: config.max_pages,
+            max_concurrency: config.max_concurrency,
             delay_ms: config.delay_ms,
             respect_robots_txt: config.respect_robots_txt,
-            robots_cache: HashMap::new(),
+            max_body_bytes: config.max_body_bytes,
+            fetch_timeout: Duration::from_secs(config.fetch_timeout_secs),
+            robots_cache: Arc::new(Mutex::new(HashMap::new())),
+            host_gate: Arc::new(Mutex::new(HashMap::new())),
+            semaphore: Arc::new(Semaphore::new(config.max_concurrency)),
+            allowed_domains: config.allowed_domains.iter().cloned().collect(),
+            weed_domains: config.weed_domains.iter().cloned().collect(),
+            respect_meta_robots: config.respect_meta_robots,
+            page_budget: config.page_budget,
+            links_per_page_budget: config.links_per_page_budget,
+            max_level: config.max_level,
+            accepted_content_types: config.accepted_content_types.clone(),
         }
     }
 
-    pub async fn crawl(&mut self, seed_urls: Vec<String>, database: &mut WebsiteCrawlerDatabase) -> Result<CrawlResult, CrawlError> {
+    /// True when `url`'s host is allowed to be crawled under `allowed_domains`/`weed_domains`.
+    fn is_domain_allowed(&self, url: &str) -> bool {
+        let Ok(parsed) = Url::parse(url) else { return false };
+        let Some(host) = parsed.host_str() else { return false };
+
+        if self.weed_domains.iter().any(|d| host_matches(host, d)) {
+            return false;
+        }
+        self.allowed_domains.is_empty() || self.allowed_domains.iter().any(|d| host_matches(host, d))
+    }
+
+    pub async fn crawl(&self, seed_urls: Vec<String>, database: &WebsiteCrawlerDatabase) -> Result<CrawlResult, CrawlError> {
         println!("🔍 Debug: Starting crawl with {} seed URLs", seed_urls.len());
         for (i, url) in seed_urls.iter().enumerate() {
             println!("🔍 Debug: Seed URL {}: {}", i + 1, url);
@@ -77,93 +161,171 @@ impl WebsiteCrawler {
         let mut crawled_pages = Vec::new();
         let mut errors = Vec::new();
 
+        // `page_budget`/`max_level` are additional, optional caps layered on top of the
+        // always-present `max_pages`/`max_depth`.
+        let effective_max_pages = self.page_budget.map_or(self.max_pages, |b| b.min(self.max_pages));
+        let effective_max_depth = self.max_level.map_or(self.max_depth, |l| l.min(self.max_depth));
+
+        // Discover XML sitemaps for each seed origin (via robots.txt `Sitemap:` directives
+        // and the conventional `/sitemap.xml`) and seed the queue from their entries, so
+        // coverage isn't limited to what `extract_urls` finds by following links.
+        let mut seed_origins = HashSet::new();
+        for seed in &seed_urls {
+            if let Ok(parsed) = Url::parse(seed) {
+                seed_origins.insert(parsed.origin().unicode_serialization());
+            }
+        }
+        for origin in &seed_origins {
+            for sitemap_url in self.discover_sitemap_urls(origin).await {
+                for entry in self.fetch_sitemap_entries(&sitemap_url).await {
+                    if !self.is_domain_allowed(&entry.loc) {
+                        continue;
+                    }
+                    let priority = entry.priority.map(|p| (p * 10.0).round() as i32).unwrap_or(5);
+                    url_queue.push(UrlPriority {
+                        url: entry.loc,
+                        priority,
+                        depth: 0,
+                        lastmod: entry.lastmod,
+                    });
+                }
+            }
+        }
+
         // Initialize queue with seed URLs
         for url in seed_urls {
+            if !self.is_domain_allowed(&url) {
+                debug!("Skipping seed URL outside allowed/weed domains: {}", url);
+                continue;
+            }
             println!("🔍 Debug: Adding to queue: {}", url);
             url_queue.push(UrlPriority {
                 url,
                 priority: 10, // High priority for seed URLs
                 depth: 0,
+                lastmod: None,
             });
         }
 
         println!("🔍 Debug: Initial queue size: {}", url_queue.len());
 
-        while let Some(url_priority) = url_queue.pop() {
-            println!("🔍 Debug: Processing URL: {} (depth: {}, priority: {})", url_priority.url, url_priority.depth, url_priority.priority);
-
-            if visited_urls.len() >= self.max_pages || url_priority.depth > self.max_depth {
-                println!("🔍 Debug: Skipping due to limits - visited: {}, max_pages: {}, depth: {}, max_depth: {}",
-                    visited_urls.len(), self.max_pages, url_priority.depth, self.max_depth);
-                continue;
-            }
-
-            let url = url_priority.url;
-
-            if visited_urls.contains(&url) {
-                println!("🔍 Debug: Already visited: {}", url);
-                continue;
-            }
-
-            visited_urls.insert(url.clone());
-            println!("🔍 Debug: Marked as visited: {}", url);
-
-            // Check robots.txt if enabled
-            if self.respect_robots_txt {
-                if let Ok(false) = self.can_crawl(&url).await {
+        // Dispatch the frontier in rounds of up to `max_concurrency` fetches at a time
+        // instead of one URL at a time. Each task waits for its own host's politeness
+        // slot independently (see `wait_for_host_slot`), so a slow host only stalls the
+        // tasks assigned to it, not the whole round.
+        while !url_queue.is_empty() && visited_urls.len() < effective_max_pages {
+            let mut batch = Vec::new();
+            while batch.len() < self.max_concurrency {
+                let Some(url_priority) = url_queue.pop() else { break };
+
+                if visited_urls.len() + batch.len() >= effective_max_pages || url_priority.depth > effective_max_depth {
+                    println!("🔍 Debug: Skipping due to limits - visited: {}, max_pages: {}, depth: {}, max_depth: {}",
+                        visited_urls.len(), effective_max_pages, url_priority.depth, effective_max_depth);
                     continue;
                 }
+                if visited_urls.contains(&url_priority.url) {
+                    println!("🔍 Debug: Already visited: {}", url_priority.url);
+                    continue;
+                }
+
+                visited_urls.insert(url_priority.url.clone());
+                println!("🔍 Debug: Marked as visited: {}", url_priority.url);
+                batch.push(url_priority);
             }
 
-            // Add delay between requests
-            if self.delay_ms > 0 {
-                tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+            if batch.is_empty() {
+                break;
             }
 
-            match self.crawl_single_page(&url, url_priority.depth).await {
-                Ok(page) => {
-                    println!("🔍 Debug: Successfully crawled page: {} (status: {}, size: {} bytes)",
-                        page.url, page.http_status_code, page.page_size);
+            let tasks: Vec<_> = batch.into_iter().map(|url_priority| {
+                let crawler = self.clone();
+                let database = database.clone();
+                async move {
+                    let url = url_priority.url.clone();
 
-                    // Store page in database
-                    if let Err(e) = database.store_crawled_page(&page, &session_id) {
-                        println!("🔍 Debug: Database error storing page: {:?}", e);
-                        errors.push(CrawlError::DatabaseError(e.to_string()));
-                        continue;
+                    if crawler.respect_robots_txt {
+                        if let Ok(false) = crawler.can_crawl(&url).await {
+                            return (url_priority, None);
+                        }
                     }
 
-                    crawled_pages.push(page.clone());
-
-                    // Extract and queue new URLs
-                    if url_priority.depth < self.max_depth {
-                        let new_urls = self.extract_urls(&page.content_html, &url);
-                        println!("🔍 Debug: Extracted {} new URLs from {}", new_urls.len(), url);
-                        for new_url in new_urls {
-                            if !visited_urls.contains(&new_url) {
-                                let priority = self.calculate_url_priority(&new_url, &url);
-                                url_queue.push(UrlPriority {
-                                    url: new_url,
-                                    priority,
-                                    depth: url_priority.depth + 1,
-                                });
+                    let _permit = crawler.semaphore.acquire().await.expect("semaphore is never closed");
+                    crawler.wait_for_host_slot(&url).await;
+                    let outcome = crawler.crawl_single_page(&url, url_priority.depth, &database).await;
+                    (url_priority, Some(outcome))
+                }
+            }).collect();
+
+            for (url_priority, outcome) in join_all(tasks).await {
+                let url = url_priority.url;
+
+                match outcome {
+                    None => continue, // disallowed by robots.txt
+                    Some(Ok(page)) => {
+                        println!("🔍 Debug: Successfully crawled page: {} (status: {}, size: {} bytes)",
+                            page.url, page.http_status_code, page.page_size);
+
+                        // Mark every intermediate hop (and the final landing URL) as visited so
+                        // the queue never re-fetches a URL we reached only via redirect.
+                        for hop in &page.redirect_chain {
+                            if let Some((_, hop_url)) = hop.split_once(' ') {
+                                visited_urls.insert(hop_url.to_string());
+                            }
+                        }
+                        visited_urls.insert(page.url.clone());
+
+                        let directives = if self.respect_meta_robots {
+                            RobotsDirectives::parse(&page.robots_meta)
+                        } else {
+                            RobotsDirectives::default()
+                        };
+
+                        // noindex: keep the fetch in the in-memory result, but don't persist it
+                        if directives.noindex {
+                            println!("🔍 Debug: noindex directive present, skipping store for: {}", url);
+                        } else if let Err(e) = database.store_crawled_page(&page, &session_id) {
+                            println!("🔍 Debug: Database error storing page: {:?}", e);
+                            errors.push(CrawlError::DatabaseError(e.to_string()));
+                            continue;
+                        }
+
+                        crawled_pages.push(page.clone());
+
+                        // Extract and queue new URLs, unless nofollow says not to spend the frontier on this page
+                        if url_priority.depth < effective_max_depth && !directives.nofollow {
+                            let mut new_urls = self.extract_urls(&page.content_html, &url);
+                            if let Some(budget) = self.links_per_page_budget {
+                                new_urls.truncate(budget);
+                            }
+                            println!("🔍 Debug: Extracted {} new URLs from {}", new_urls.len(), url);
+                            for new_url in new_urls {
+                                if !visited_urls.contains(&new_url) && self.is_domain_allowed(&new_url) {
+                                    let priority = self.calculate_url_priority(&new_url, &url);
+                                    url_queue.push(UrlPriority {
+                                        url: new_url,
+                                        priority,
+                                        depth: url_priority.depth + 1,
+                                        lastmod: None,
+                                    });
+                                }
                             }
                         }
                     }
-                }
-                Err(e) => {
-                    println!("🔍 Debug: Error crawling page {}: {:?}", url, e);
-
-                    // Log error
-                    if let Err(db_err) = database.log_crawl_error(
-                        &session_id,
-                        &url,
-                        &format!("{:?}", e),
-                        &e.to_string(),
-                        None,
-                    ) {
-                        errors.push(CrawlError::DatabaseError(db_err.to_string()));
+                    Some(Err(e)) => {
+                        println!("🔍 Debug: Error crawling page {}: {:?}", url, e);
+
+                        // Log error
+                        if let Err(db_err) = database.log_crawl_error(
+                            &session_id,
+                            &url,
+                            &format!("{:?}", e),
+                            &e.to_string(),
+                            None,
+                        ) {
+                            errors.push(CrawlError::DatabaseError(db_err.to_string()));
+                        }
+                        errors.push(e);
                     }
-                    errors.push(e);
                 }
             }
         }
@@ -179,69 +341,295 @@ impl WebsiteCrawler {
         })
     }
 
-    async fn crawl_single_page(&self, url: &str, _depth: usize) -> Result<CrawledPage, CrawlError> {
+    /// Fetches `url`, following any redirects manually (the client is built with
+    /// `redirect::Policy::none()`) so each hop can be recorded into `redirect_chain`
+    /// and loops/overlong chains can be caught instead of silently followed. The whole
+    /// fetch (redirects + body) is bounded by `fetch_timeout` and `max_body_bytes` so a
+    /// slow or huge response can't stall or blow up the crawler. If `database` has a
+    /// stored `ETag`/`Last-Modified` for `url`, the initial request is conditional; a
+    /// `304 Not Modified` short-circuits into the previously stored page instead of
+    /// re-downloading and re-parsing it.
+    async fn crawl_single_page(&self, url: &str, _depth: usize, database: &WebsiteCrawlerDatabase) -> Result<CrawledPage, CrawlError> {
         println!("🔍 Debug: Starting to crawl single page: {}", url);
         let start_time = Instant::now();
+        let prior_validators = database.get_page_validators(url).ok().flatten();
+
+        let fetch = async {
+            let mut current_url = url.to_string();
+            let mut redirect_chain = Vec::new();
+            let mut hops_seen: HashSet<String> = HashSet::new();
+            hops_seen.insert(current_url.clone());
+
+            let response = loop {
+                let mut request = self.client.get(&current_url);
+                if current_url == url {
+                    if let Some((etag, last_modified)) = &prior_validators {
+                        if !etag.is_empty() {
+                            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+                        }
+                        if !last_modified.is_empty() {
+                            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+                        }
+                    }
+                }
 
-        let response = self.client.get(url).send().await?;
-        let status_code = response.status().as_u16();
-        let response_time = start_time.elapsed();
-        let charset = self.detect_charset_from_headers(&response);
+                let response = request.send().await?;
+                let status = response.status();
+
+                if status.is_redirection() {
+                    let location = response.headers()
+                        .get(reqwest::header::LOCATION)
+                        .and_then(|v| v.to_str().ok())
+                        .ok_or_else(|| CrawlError::RedirectError(
+                            url.to_string(),
+                            format!("redirect response from {} missing Location header", current_url),
+                        ))?;
+                    let next_url = Url::parse(&current_url)
+                        .and_then(|base| base.join(location))?
+                        .to_string();
+
+                    redirect_chain.push(format!("{} {}", status.as_u16(), current_url));
+
+                    if redirect_chain.len() > self.max_redirects {
+                        return Err(CrawlError::RedirectError(
+                            url.to_string(),
+                            format!("exceeded max_redirects ({})", self.max_redirects),
+                        ));
+                    }
+                    if !hops_seen.insert(next_url.clone()) {
+                        return Err(CrawlError::RedirectError(
+                            url.to_string(),
+                            format!("redirect loop detected at {}", next_url),
+                        ));
+                    }
 
-        println!("🔍 Debug: HTTP response received - status: {}, time: {:?}", status_code, response_time);
+                    current_url = next_url;
+                    continue;
+                }
 
-        if !response.status().is_success() {
-            println!("🔍 Debug: HTTP error - status: {}", status_code);
-            return Err(CrawlError::HttpError(status_code, response.status().to_string()));
-        }
+                break response;
+            };
+
+            let final_url = current_url;
+            let status_code = response.status().as_u16();
+            let charset = self.detect_charset_from_headers(&response);
+            let content_type = response.headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string();
+            let x_robots_tag = response.headers()
+                .get("x-robots-tag")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string();
+            let etag = response.headers()
+                .get(reqwest::header::ETAG)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string();
+            let last_modified = response.headers()
+                .get(reqwest::header::LAST_MODIFIED)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string();
+
+            if status_code == 304 {
+                return Ok(FetchOutcome::NotModified(final_url));
+            }
 
-        let html = response.text().await?;
-        println!("🔍 Debug: Downloaded HTML content - size: {} bytes", html.len());
-        let document = Html::parse_document(&html);
+            if !response.status().is_success() {
+                println!("🔍 Debug: HTTP error - status: {}", status_code);
+                return Err(CrawlError::HttpError(status_code, response.status().to_string()));
+            }
+
+            // Reject disallowed content types after headers arrive, before the body streams.
+            let base_content_type = content_type.split(';').next().unwrap_or("").trim().to_string();
+            if !self.accepted_content_types.is_empty() && !base_content_type.is_empty()
+                && !self.accepted_content_types.iter().any(|t| t.eq_ignore_ascii_case(&base_content_type))
+            {
+                println!("🔍 Debug: Rejecting content-type {} for {}", content_type, final_url);
+                return Err(CrawlError::RejectedContentType(final_url, content_type));
+            }
+
+            // Stream the body instead of buffering it in one shot, aborting as soon as it
+            // crosses `max_body_bytes` rather than after the whole thing has downloaded.
+            let mut body = Vec::new();
+            let mut stream = response.bytes_stream();
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk?;
+                body.extend_from_slice(&chunk);
+                if body.len() > self.max_body_bytes {
+                    return Err(CrawlError::TooLarge(final_url, self.max_body_bytes));
+                }
+            }
+
+            Ok(FetchOutcome::Fetched {
+                final_url, status_code, redirect_chain, charset, content_type,
+                x_robots_tag, etag, last_modified, body,
+            })
+        };
+
+        let outcome = tokio::time::timeout(self.fetch_timeout, fetch)
+            .await
+            .map_err(|_| CrawlError::TimeoutError(format!("fetching {} exceeded {:?}", url, self.fetch_timeout)))??;
+
+        let (final_url, status_code, redirect_chain, charset, content_type, x_robots_tag, etag, last_modified, body) = match outcome {
+            FetchOutcome::NotModified(final_url) => {
+                println!("🔍 Debug: 304 Not Modified for {}, reusing cached page", final_url);
+                let mut page = database.get_latest_page(&final_url)
+                    .map_err(|e| CrawlError::DatabaseError(e.to_string()))?
+                    .ok_or_else(|| CrawlError::ParseError(
+                        format!("304 Not Modified for {} but no cached copy found", final_url)
+                    ))?;
+                page.crawl_time = chrono::Utc::now();
+                page.response_time_ms = start_time.elapsed().as_millis() as u64;
+                return Ok(page);
+            }
+            FetchOutcome::Fetched { final_url, status_code, redirect_chain, charset, content_type, x_robots_tag, etag, last_modified, body } =>
+                (final_url, status_code, redirect_chain, charset, content_type, x_robots_tag, etag, last_modified, body),
+        };
+
+        let response_time = start_time.elapsed();
+        println!("🔍 Debug: HTTP response received - status: {}, time: {:?}", status_code, response_time);
+
+        let base_type = content_type.split(';').next().unwrap_or("").trim();
+        let is_html = base_type.eq_ignore_ascii_case("text/html")
+            || base_type.eq_ignore_ascii_case("application/xhtml+xml");
+
+        let html = String::from_utf8_lossy(&body).into_owned();
+        println!("🔍 Debug: Downloaded body - size: {} bytes, content-type: {}", body.len(), content_type);
+
+        let (title, meta_description, content_text, word_count, language, language_confidence,
+            h1_tags, h2_tags, meta_keywords, canonical_url, robots_meta_from_doc, images_count) = if is_html {
+            let document = Html::parse_document(&html);
+            let content_text = self.extract_text_content(&document);
+            let (language, language_confidence) = self.detect_language(&document, &content_text);
+            (
+                self.extract_title(&document),
+                self.extract_meta_description(&document),
+                content_text,
+                self.count_words(&html),
+                language,
+                language_confidence,
+                self.extract_h1_tags(&document),
+                self.extract_h2_tags(&document),
+                self.extract_meta_keywords(&document),
+                self.extract_canonical_url(&document),
+                self.extract_robots_meta(&document),
+                self.count_images(&document),
+            )
+        } else {
+            println!("🔍 Debug: Skipping HTML extraction for non-HTML content-type: {}", content_type);
+            (String::new(), String::new(), String::new(), 0, "unknown".to_string(), 0.0, vec![], vec![],
+                String::new(), String::new(), String::new(), 0)
+        };
 
         let page = CrawledPage {
-            url: url.to_string(),
+            url: final_url,
             original_url: url.to_string(),
-            redirect_chain: Vec::new(),
-            title: self.extract_title(&document),
-            meta_description: self.extract_meta_description(&document),
-            content_text: self.extract_text_content(&document),
-            content_html: html.clone(),
+            redirect_chain,
+            title,
+            meta_description,
+            content_text,
+            content_html: if is_html { html.clone() } else { String::new() },
             content_hash: self.calculate_content_hash(&html),
-            word_count: self.count_words(&html),
-            page_size: html.len(),
+            word_count,
+            page_size: body.len(),
             http_status_code: status_code,
             response_time_ms: response_time.as_millis() as u64,
-            language: self.detect_language(&document),
+            language,
+            language_confidence,
             charset,
-            h1_tags: self.extract_h1_tags(&document),
-            h2_tags: self.extract_h2_tags(&document),
-            meta_keywords: self.extract_meta_keywords(&document),
-            canonical_url: self.extract_canonical_url(&document),
-            robots_meta: self.extract_robots_meta(&document),
+            content_type,
+            h1_tags,
+            h2_tags,
+            meta_keywords,
+            canonical_url,
+            robots_meta: [robots_meta_from_doc, x_robots_tag]
+                .into_iter()
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(", "),
             internal_links_count: 0, // Will be calculated after URL extraction
             external_links_count: 0, // Will be calculated after URL extraction
-            images_count: self.count_images(&document),
+            images_count,
             crawl_time: chrono::Utc::now(),
+            etag,
+            last_modified,
         };
 
         println!("🔍 Debug: Successfully created CrawledPage for: {}", url);
         Ok(page)
     }
 
-    async fn can_crawl(&mut self, url: &str) -> Result<bool, CrawlError> {
+    async fn can_crawl(&self, url: &str) -> Result<bool, CrawlError> {
         let parsed_url = Url::parse(url)?;
         let robots_url = format!("{}/robots.txt", parsed_url.origin().unicode_serialization());
 
-        if !self.robots_cache.contains_key(&robots_url) {
-            let robots_txt = self.fetch_robots_txt(&robots_url).await?;
-            self.robots_cache.insert(robots_url.clone(), robots_txt);
+        {
+            let cache = self.robots_cache.lock().await;
+            if let Some(robots_txt) = cache.get(&robots_url) {
+                return Ok(robots_txt.can_crawl(&self.user_agent, parsed_url.path()));
+            }
         }
 
-        if let Some(robots_txt) = self.robots_cache.get(&robots_url) {
-            Ok(robots_txt.can_crawl(&self.user_agent, &parsed_url.path()))
-        } else {
-            Ok(true) // Allow crawling if robots.txt can't be fetched
+        let robots_txt = self.fetch_robots_txt(&robots_url).await?;
+        let allowed = robots_txt.can_crawl(&self.user_agent, parsed_url.path());
+        self.robots_cache.lock().await.insert(robots_url, robots_txt);
+        Ok(allowed)
+    }
+
+    /// Returns every `Sitemap:` URL discovered across all robots.txt files fetched so far.
+    pub async fn discovered_sitemaps(&self) -> Vec<String> {
+        self.robots_cache.lock().await.values().flat_map(|r| r.sitemaps().to_vec()).collect()
+    }
+
+    /// Returns the delay to wait before fetching `url`, taking the larger of the
+    /// crawler's configured `delay_ms` and the host's robots.txt `Crawl-delay` (if the
+    /// robots.txt for that host has already been fetched and cached).
+    async fn effective_delay_ms(&self, url: &str) -> u64 {
+        let Ok(parsed_url) = Url::parse(url) else {
+            return self.delay_ms;
+        };
+        let robots_url = format!("{}/robots.txt", parsed_url.origin().unicode_serialization());
+
+        let cache = self.robots_cache.lock().await;
+        match cache.get(&robots_url).and_then(|r| r.crawl_delay_for(&self.user_agent)) {
+            Some(crawl_delay_secs) => ((crawl_delay_secs * 1000.0) as u64).max(self.delay_ms),
+            None => self.delay_ms,
+        }
+    }
+
+    /// Blocks the calling task until `url`'s host has gone at least `effective_delay_ms`
+    /// since the last request we sent it. Hosts are tracked independently, so one slow
+    /// or rate-limited origin never delays fetches to other hosts running concurrently.
+    async fn wait_for_host_slot(&self, url: &str) {
+        let Some(host) = Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string)) else {
+            return;
+        };
+        let delay = Duration::from_millis(self.effective_delay_ms(url).await);
+        if delay.is_zero() {
+            return;
+        }
+
+        loop {
+            let now = Instant::now();
+            let wait = {
+                let mut gate = self.host_gate.lock().await;
+                let next_allowed = gate.get(&host).copied().unwrap_or(now);
+                if now >= next_allowed {
+                    gate.insert(host.clone(), now + delay);
+                    None
+                } else {
+                    Some(next_allowed - now)
+                }
+            };
+
+            match wait {
+                None => return,
+                Some(remaining) => tokio::time::sleep(remaining).await,
+            }
         }
     }
 
@@ -255,6 +643,95 @@ impl WebsiteCrawler {
         }
     }
 
+    /// Discovers sitemap entries for every distinct origin among `seed_urls` (robots.txt
+    /// `Sitemap:` directives plus the conventional `/sitemap.xml`, with sitemap-index files
+    /// expanded recursively) and returns the domain-allowed `<loc>` URLs found, capped at
+    /// `page_budget` if one is configured. Callers merge the result into their own seed set
+    /// before crawling, so discovery isn't limited to the handful of URLs they started with.
+    pub(crate) async fn discover_seed_urls_from_sitemaps(&self, seed_urls: &[String]) -> Vec<String> {
+        let mut seed_origins = HashSet::new();
+        for seed in seed_urls {
+            if let Ok(parsed) = Url::parse(seed) {
+                seed_origins.insert(parsed.origin().unicode_serialization());
+            }
+        }
+
+        let budget = self.page_budget.unwrap_or(usize::MAX);
+        let mut discovered = Vec::new();
+        let mut seen = HashSet::new();
+        for origin in &seed_origins {
+            for sitemap_url in self.discover_sitemap_urls(origin).await {
+                for entry in self.fetch_sitemap_entries(&sitemap_url).await {
+                    if discovered.len() >= budget {
+                        return discovered;
+                    }
+                    if !self.is_domain_allowed(&entry.loc) || !seen.insert(entry.loc.clone()) {
+                        continue;
+                    }
+                    discovered.push(entry.loc);
+                }
+            }
+        }
+
+        discovered
+    }
+
+    /// Returns candidate sitemap URLs for `origin`: every `Sitemap:` directive from its
+    /// robots.txt plus the conventional `/sitemap.xml` fallback.
+    async fn discover_sitemap_urls(&self, origin: &str) -> Vec<String> {
+        let robots_url = format!("{}/robots.txt", origin);
+        let mut candidates = match self.fetch_robots_txt(&robots_url).await {
+            Ok(robots) => robots.sitemaps().to_vec(),
+            Err(_) => Vec::new(),
+        };
+        candidates.push(format!("{}/sitemap.xml", origin));
+        candidates
+    }
+
+    /// Fetches `sitemap_url` and, recursively (bounded by `MAX_SITEMAP_RECURSION_DEPTH`
+    /// and `MAX_SITEMAPS_FOLLOWED`), any sitemap-index entries it references, returning
+    /// every `<url>` entry found along the way.
+    async fn fetch_sitemap_entries(&self, sitemap_url: &str) -> Vec<SitemapEntry> {
+        let mut entries = Vec::new();
+        let mut fetched = HashSet::new();
+        let mut frontier = vec![(sitemap_url.to_string(), 0usize)];
+
+        while let Some((url, depth)) = frontier.pop() {
+            if fetched.len() >= MAX_SITEMAPS_FOLLOWED || depth > MAX_SITEMAP_RECURSION_DEPTH {
+                continue;
+            }
+            if !fetched.insert(url.clone()) {
+                continue;
+            }
+
+            let Some(body) = self.fetch_sitemap_body(&url).await else { continue };
+            let (url_entries, index_locs) = parse_sitemap_xml(&body);
+            entries.extend(url_entries);
+            frontier.extend(index_locs.into_iter().map(|loc| (loc, depth + 1)));
+        }
+
+        entries
+    }
+
+    /// Downloads a sitemap body, transparently gunzipping `.xml.gz` sitemaps.
+    async fn fetch_sitemap_body(&self, url: &str) -> Option<String> {
+        let response = self.client.get(url).send().await.ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        let bytes = response.bytes().await.ok()?;
+
+        if url.ends_with(".gz") {
+            use std::io::Read;
+            let mut decoder = flate2::read::GzDecoder::new(&bytes[..]);
+            let mut decompressed = String::new();
+            decoder.read_to_string(&mut decompressed).ok()?;
+            Some(decompressed)
+        } else {
+            String::from_utf8(bytes.to_vec()).ok()
+        }
+    }
+
     fn extract_title(&self, document: &Html) -> String {
         let selector = Selector::parse("title").unwrap();
         document.select(&selector)
@@ -304,13 +781,29 @@ impl WebsiteCrawler {
         text_content.split_whitespace().count()
     }
 
-    fn detect_language(&self, document: &Html) -> String {
+    /// Returns the page's language and a confidence score. Prefers the declared
+    /// `<html lang>` attribute (confidence `1.0`); otherwise falls back to a trigram
+    /// n-gram classifier run over `content_text`, or `("unknown", 0.0)` if there isn't
+    /// enough text to classify reliably.
+    fn detect_language(&self, document: &Html, content_text: &str) -> (String, f32) {
         let selector = Selector::parse("html").unwrap();
-        document.select(&selector)
+        if let Some(lang) = document.select(&selector)
             .next()
             .and_then(|el| el.value().attr("lang"))
-            .unwrap_or("en")
-            .to_string()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+        {
+            return (lang.to_string(), 1.0);
+        }
+
+        if content_text.trim().chars().count() < MIN_LANGUAGE_DETECTION_CHARS {
+            return ("unknown".to_string(), 0.0);
+        }
+
+        match whatlang::detect(content_text) {
+            Some(info) => (info.lang().code().to_string(), info.confidence() as f32),
+            None => ("unknown".to_string(), 0.0),
+        }
     }
 
     fn detect_charset_from_headers(&self, response: &reqwest::Response) -> String {
@@ -379,6 +872,14 @@ impl WebsiteCrawler {
         let mut urls = Vec::new();
 
         for element in document.select(&selector) {
+            let is_nofollow = self.respect_meta_robots
+                && element.value().attr("rel")
+                    .map(|rel| rel.split_whitespace().any(|token| token.eq_ignore_ascii_case("nofollow")))
+                    .unwrap_or(false);
+            if is_nofollow {
+                continue;
+            }
+
             if let Some(href) = element.value().attr("href") {
                 if let Ok(absolute_url) = self.resolve_url(base_url, href) {
                     urls.push(absolute_url.to_string());
@@ -421,53 +922,287 @@ impl WebsiteCrawler {
     }
 }
 
-#[derive(Debug, Default)]
+impl Clone for WebsiteCrawler {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            user_agent: self.user_agent.clone(),
+            timeout: self.timeout,
+            max_redirects: self.max_redirects,
+            max_depth: self.max_depth,
+            max_pages: self.max_pages,
+            max_concurrency: self.max_concurrency,
+            delay_ms: self.delay_ms,
+            respect_robots_txt: self.respect_robots_txt,
+            max_body_bytes: self.max_body_bytes,
+            fetch_timeout: self.fetch_timeout,
+            respect_meta_robots: self.respect_meta_robots,
+            page_budget: self.page_budget,
+            links_per_page_budget: self.links_per_page_budget,
+            max_level: self.max_level,
+            accepted_content_types: self.accepted_content_types.clone(),
+            robots_cache: self.robots_cache.clone(),
+            host_gate: self.host_gate.clone(),
+            semaphore: self.semaphore.clone(),
+            allowed_domains: self.allowed_domains.clone(),
+            weed_domains: self.weed_domains.clone(),
+        }
+    }
+}
+
+/// One `User-agent:` record from a robots.txt file: the agent tokens it applies to,
+/// its ordered `Allow`/`Disallow` rules, and an optional `Crawl-delay`.
+#[derive(Debug, Default, Clone)]
+struct RobotsGroup {
+    user_agents: Vec<String>,
+    rules: Vec<(bool, String)>, // (is_allow, pattern)
+    crawl_delay: Option<f64>,
+}
+
+/// RFC 9309 compliant robots.txt matcher: resolves the most specific user-agent group,
+/// then applies `*`/`$` wildcard pattern matching with longest-match-wins (Allow breaks ties).
+#[derive(Debug, Default, Clone)]
 struct RobotsTxt {
-    rules: HashMap<String, Vec<String>>,
+    groups: Vec<RobotsGroup>,
+    sitemaps: Vec<String>,
 }
 
 impl RobotsTxt {
     fn parse(content: &str) -> Self {
-        let mut rules = HashMap::new();
-        let mut current_user_agent = "*".to_string();
-
-        for line in content.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
+        let mut groups: Vec<RobotsGroup> = Vec::new();
+        let mut sitemaps = Vec::new();
+        let mut current: Option<RobotsGroup> = None;
+        // A run of `User-agent:` lines belongs to one group; the first Allow/Disallow/
+        // Crawl-delay line after them closes the group to further agent tokens.
+        let mut awaiting_agents = true;
+
+        for raw_line in content.lines() {
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
                 continue;
             }
-
-            if line.to_lowercase().starts_with("user-agent:") {
-                current_user_agent = line.split(':').nth(1).unwrap_or("*").trim().to_string();
-            } else if line.to_lowercase().starts_with("disallow:") {
-                if let Some(path) = line.split(':').nth(1) {
-                    let path = path.trim();
-                    if !path.is_empty() {
-                        rules.entry(current_user_agent.clone())
-                            .or_insert_with(Vec::new)
-                            .push(path.to_string());
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+
+            match key.as_str() {
+                "user-agent" => {
+                    if !awaiting_agents {
+                        if let Some(group) = current.take() {
+                            groups.push(group);
+                        }
+                    }
+                    current.get_or_insert_with(RobotsGroup::default)
+                        .user_agents.push(value.to_string());
+                    awaiting_agents = true;
+                }
+                "allow" => {
+                    if let Some(group) = current.as_mut() {
+                        if !value.is_empty() {
+                            group.rules.push((true, value.to_string()));
+                        }
+                        awaiting_agents = false;
+                    }
+                }
+                "disallow" => {
+                    if let Some(group) = current.as_mut() {
+                        if !value.is_empty() {
+                            group.rules.push((false, value.to_string()));
+                        }
+                        awaiting_agents = false;
                     }
                 }
+                "crawl-delay" => {
+                    if let Some(group) = current.as_mut() {
+                        group.crawl_delay = value.parse::<f64>().ok();
+                        awaiting_agents = false;
+                    }
+                }
+                "sitemap" => {
+                    sitemaps.push(value.to_string());
+                }
+                _ => {}
             }
         }
+        if let Some(group) = current.take() {
+            groups.push(group);
+        }
 
-        Self { rules }
+        Self { groups, sitemaps }
+    }
+
+    /// Picks the group whose user-agent token is the longest case-insensitive prefix
+    /// match of `user_agent`, falling back to the `*` group.
+    fn select_group(&self, user_agent: &str) -> Option<&RobotsGroup> {
+        let ua_lower = user_agent.to_ascii_lowercase();
+        let mut best: Option<(usize, &RobotsGroup)> = None;
+        let mut wildcard: Option<&RobotsGroup> = None;
+
+        for group in &self.groups {
+            for token in &group.user_agents {
+                if token == "*" {
+                    wildcard = Some(group);
+                    continue;
+                }
+                let token_lower = token.to_ascii_lowercase();
+                if ua_lower.starts_with(&token_lower)
+                    && best.map_or(true, |(best_len, _)| token_lower.len() > best_len)
+                {
+                    best = Some((token_lower.len(), group));
+                }
+            }
+        }
+
+        best.map(|(_, group)| group).or(wildcard)
+    }
+
+    /// Translates a robots.txt pattern (`*` wildcard, optional trailing `$` anchor) into
+    /// a regex and checks whether it matches the start of `path`.
+    fn pattern_matches(pattern: &str, path: &str) -> bool {
+        let anchored_end = pattern.ends_with('$');
+        let body = if anchored_end { &pattern[..pattern.len() - 1] } else { pattern };
+
+        let mut regex_str = String::from("^");
+        for (i, part) in body.split('*').enumerate() {
+            if i > 0 {
+                regex_str.push_str(".*");
+            }
+            regex_str.push_str(&regex::escape(part));
+        }
+        if anchored_end {
+            regex_str.push('$');
+        }
+
+        Regex::new(&regex_str).map(|re| re.is_match(path)).unwrap_or(false)
     }
 
     fn can_crawl(&self, user_agent: &str, path: &str) -> bool {
-        // Check specific user agent rules first, then wildcard
-        let user_agents = [user_agent, "*"];
-
-        for ua in &user_agents {
-            if let Some(disallowed_paths) = self.rules.get(&ua.to_string()) {
-                for disallowed in disallowed_paths {
-                    if path.starts_with(disallowed) {
-                        return false;
+        let Some(group) = self.select_group(user_agent) else {
+            return true;
+        };
+
+        // Longest matching pattern wins; on a length tie, Allow wins.
+        let mut best: Option<(usize, bool)> = None;
+        for (is_allow, pattern) in &group.rules {
+            if !Self::pattern_matches(pattern, path) {
+                continue;
+            }
+            let len = pattern.len();
+            best = Some(match best {
+                None => (len, *is_allow),
+                Some((best_len, best_allow)) if len > best_len => (len, *is_allow),
+                Some((best_len, best_allow)) if len == best_len => (best_len, best_allow || *is_allow),
+                Some(existing) => existing,
+            });
+        }
+
+        best.map(|(_, allow)| allow).unwrap_or(true)
+    }
+
+    /// Returns the `Crawl-delay` (in seconds) from the group matching `user_agent`, if any.
+    fn crawl_delay_for(&self, user_agent: &str) -> Option<f64> {
+        self.select_group(user_agent).and_then(|group| group.crawl_delay)
+    }
+
+    /// Returns every `Sitemap:` URL discovered in this robots.txt.
+    fn sitemaps(&self) -> &[String] {
+        &self.sitemaps
+    }
+}
+
+/// `noindex`/`nofollow` flags parsed from a page's combined `<meta name="robots">`
+/// content and `X-Robots-Tag` response header.
+#[derive(Debug, Default, Clone, Copy)]
+struct RobotsDirectives {
+    noindex: bool,
+    nofollow: bool,
+}
+
+impl RobotsDirectives {
+    fn parse(directives: &str) -> Self {
+        let lower = directives.to_ascii_lowercase();
+        Self {
+            noindex: lower.split(',').any(|d| d.trim() == "noindex"),
+            nofollow: lower.split(',').any(|d| d.trim() == "nofollow"),
+        }
+    }
+}
+
+/// One `<url>` entry from an XML sitemap: its location plus the optional metadata used
+/// to prioritize and order the crawl.
+struct SitemapEntry {
+    loc: String,
+    lastmod: Option<DateTime<Utc>>,
+    priority: Option<f64>,
+}
+
+/// Parses an XML sitemap or sitemap-index body, returning the `<url>` entries found
+/// (with `<loc>`/`<lastmod>`/`<priority>`) plus any `<sitemap><loc>` entries to recurse into.
+fn parse_sitemap_xml(xml: &str) -> (Vec<SitemapEntry>, Vec<String>) {
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    let mut entries = Vec::new();
+    let mut index_locs = Vec::new();
+    let mut in_sitemap_index_entry = false;
+    let mut current_tag = String::new();
+    let mut loc: Option<String> = None;
+    let mut lastmod: Option<String> = None;
+    let mut priority: Option<String> = None;
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                match name.as_str() {
+                    "sitemap" => in_sitemap_index_entry = true,
+                    "url" => in_sitemap_index_entry = false,
+                    _ => {}
+                }
+                current_tag = name;
+            }
+            Ok(Event::Text(text)) => {
+                let value = text.unescape().map(|s| s.to_string()).unwrap_or_default();
+                match current_tag.as_str() {
+                    "loc" => loc = Some(value),
+                    "lastmod" => lastmod = Some(value),
+                    "priority" => priority = Some(value),
+                    _ => {}
+                }
+            }
+            Ok(Event::End(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                if name == "sitemap" || name == "url" {
+                    if let Some(loc_value) = loc.take() {
+                        if in_sitemap_index_entry {
+                            index_locs.push(loc_value);
+                        } else {
+                            entries.push(SitemapEntry {
+                                loc: loc_value,
+                                lastmod: lastmod.take()
+                                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                                    .map(|dt| dt.with_timezone(&Utc)),
+                                priority: priority.take().and_then(|s| s.parse::<f64>().ok()),
+                            });
+                        }
                     }
+                    lastmod = None;
+                    priority = None;
                 }
+                current_tag.clear();
             }
+            Ok(Event::Eof) => break,
+            Err(_) => break,
+            _ => {}
         }
-
-        true
+        buf.clear();
     }
+
+    (entries, index_locs)
 }
@@ -1,12 +1,13 @@
-use crate::models::{CrawlResult, CrawlerConfig};
+use crate::models::{CrawlResult, CrawlerConfig, SanitizePolicy};
 use crate::database::Database;
 use anyhow::Result;
 use chrono::Utc;
 use futures::future::join_all;
+use futures::StreamExt;
 use log::{info, warn, error, debug};
 use reqwest::{Client, ClientBuilder};
 use scraper::{Html, Selector};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Semaphore;
@@ -15,12 +16,140 @@ use url::Url;
 use rand::seq::SliceRandom;
 use sha2::{Sha256, Digest};
 
+/// How long a host's cached `robots.txt` ruleset stays valid before it's re-fetched.
+const ROBOTS_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Below this many characters, `whatlang`'s n-gram classifier is too unreliable to trust.
+const MIN_LANGUAGE_DETECTION_CHARS: usize = 50;
+
+/// Caps on sitemap-index recursion so a pathological or hostile sitemap chain can't stall
+/// the frontier: how many levels of nested `<sitemap>` indexes are followed, and how many
+/// distinct sitemap URLs are fetched in total per origin.
+const MAX_SITEMAP_RECURSION_DEPTH: usize = 3;
+const MAX_SITEMAPS_FOLLOWED: usize = 50;
+
+/// A host's parsed `robots.txt`, reduced to the disallowed path prefixes for our user
+/// agent and its `Crawl-delay`, if any. A missing or erroring `robots.txt` is treated as
+/// an all-allow empty ruleset via `Default`.
+#[derive(Clone, Default)]
+struct RobotsRules {
+    disallow: Vec<String>,
+    crawl_delay: Option<f64>,
+    /// Every `Sitemap:` directive in the file, regardless of which `User-agent` group (if
+    /// any) it appeared under — sitemaps apply to all crawlers.
+    sitemaps: Vec<String>,
+}
+
+impl RobotsRules {
+    /// Parses `robots.txt` content, keeping only the rules from the group whose
+    /// `User-agent` token is the longest case-insensitive prefix match of `user_agent`
+    /// (falling back to the `*` group).
+    fn parse(content: &str, user_agent: &str) -> Self {
+        let ua_lower = user_agent.to_ascii_lowercase();
+        let mut current_tokens: Vec<String> = Vec::new();
+        let mut best_match_len: Option<usize> = None;
+        let mut wildcard = RobotsRules::default();
+        let mut best = RobotsRules::default();
+        let mut sitemaps = Vec::new();
+        // A `User-agent:` line that follows rule lines starts a new group.
+        let mut after_rules = false;
+
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+            if matches!(key.as_str(), "allow" | "disallow" | "crawl-delay") {
+                after_rules = true;
+            }
+
+            match key.as_str() {
+                "user-agent" => {
+                    if after_rules {
+                        current_tokens.clear();
+                        after_rules = false;
+                    }
+                    current_tokens.push(value.to_ascii_lowercase());
+                }
+                "disallow" if !value.is_empty() => {
+                    for token in &current_tokens {
+                        if token == "*" {
+                            wildcard.disallow.push(value.to_string());
+                        } else if ua_lower.starts_with(token.as_str()) {
+                            if best_match_len.map_or(true, |len| token.len() > len) {
+                                best_match_len = Some(token.len());
+                                best = RobotsRules::default();
+                            }
+                            if best_match_len == Some(token.len()) {
+                                best.disallow.push(value.to_string());
+                            }
+                        }
+                    }
+                }
+                "crawl-delay" => {
+                    let delay = value.parse::<f64>().ok();
+                    for token in &current_tokens {
+                        if token == "*" {
+                            wildcard.crawl_delay = delay;
+                        } else if ua_lower.starts_with(token.as_str()) && best_match_len == Some(token.len()) {
+                            best.crawl_delay = delay;
+                        }
+                    }
+                }
+                "sitemap" if !value.is_empty() => sitemaps.push(value.to_string()),
+                _ => {}
+            }
+        }
+
+        let mut rules = if best_match_len.is_some() { best } else { wildcard };
+        rules.sitemaps = sitemaps;
+        rules
+    }
+
+    fn is_disallowed(&self, path: &str) -> bool {
+        self.disallow.iter().any(|prefix| !prefix.is_empty() && path.starts_with(prefix.as_str()))
+    }
+}
+
+/// True when `host` is exactly `domain` or a subdomain of it.
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// `noindex`/`nofollow` flags parsed from a page's combined `<meta name="robots">`
+/// content and `X-Robots-Tag` response header.
+struct RobotsDirectives {
+    noindex: bool,
+    nofollow: bool,
+}
+
+impl RobotsDirectives {
+    fn parse(directives: &str) -> Self {
+        let lower = directives.to_ascii_lowercase();
+        Self {
+            noindex: lower.split(',').any(|d| d.trim() == "noindex"),
+            nofollow: lower.split(',').any(|d| d.trim() == "nofollow"),
+        }
+    }
+}
+
 pub struct Crawler {
     client: Client,
     database: Arc<Database>,
     config: CrawlerConfig,
     semaphore: Arc<Semaphore>,
     visited_urls: Arc<tokio::sync::Mutex<HashSet<String>>>,
+    /// Per-host cached `robots.txt` rules, expired after `ROBOTS_CACHE_TTL`.
+    robots_cache: Arc<tokio::sync::Mutex<HashMap<String, (RobotsRules, Instant)>>>,
+    /// Per-host "earliest next request" timestamps, so concurrent tasks still space out
+    /// requests to the same host instead of relying on the global `delay_between_requests_ms`.
+    host_gate: Arc<tokio::sync::Mutex<HashMap<String, Instant>>>,
+    /// Origins whose sitemaps have already been discovered and fed into the frontier, so a
+    /// host visited across multiple batches only pays for sitemap discovery once.
+    sitemap_origins_seen: Arc<tokio::sync::Mutex<HashSet<String>>>,
+    /// Tag/attribute allowlist `sanitize_html` cleans fetched pages through before deriving
+    /// `content_text`/`content_html`.
+    sanitize_policy: SanitizePolicy,
 }
 
 impl Crawler {
@@ -30,6 +159,9 @@ impl Crawler {
             .user_agent(&config.user_agents[0])
             .gzip(true)
             .brotli(true)
+            // Redirects are followed manually in `crawl_url` so every hop can be recorded
+            // into `redirect_chain` instead of being silently collapsed into the final URL.
+            .redirect(reqwest::redirect::Policy::none())
             .build()?;
 
         Ok(Self {
@@ -38,36 +170,239 @@ impl Crawler {
             semaphore: Arc::new(Semaphore::new(config.max_concurrent_requests)),
             config,
             visited_urls: Arc::new(tokio::sync::Mutex::new(HashSet::new())),
+            robots_cache: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            host_gate: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            sitemap_origins_seen: Arc::new(tokio::sync::Mutex::new(HashSet::new())),
+            sanitize_policy: SanitizePolicy::default(),
         })
     }
 
+    /// Overrides the default tag/attribute allowlist `sanitize_html` cleans pages through.
+    pub fn with_sanitize_policy(mut self, policy: SanitizePolicy) -> Self {
+        self.sanitize_policy = policy;
+        self
+    }
+
+    /// Fetches (or returns the still-fresh cached) `robots.txt` rules for `url`'s host.
+    async fn robots_rules_for(&self, url: &Url) -> RobotsRules {
+        let robots_url = format!("{}/robots.txt", url.origin().unicode_serialization());
+
+        {
+            let cache = self.robots_cache.lock().await;
+            if let Some((rules, fetched_at)) = cache.get(&robots_url) {
+                if fetched_at.elapsed() < ROBOTS_CACHE_TTL {
+                    return rules.clone();
+                }
+            }
+        }
+
+        let user_agent = self.config.user_agents.first().map(String::as_str).unwrap_or("*");
+        let rules = match self.client.get(&robots_url).send().await {
+            Ok(response) if response.status().is_success() => {
+                let body = response.text().await.unwrap_or_default();
+                RobotsRules::parse(&body, user_agent)
+            }
+            _ => RobotsRules::default(),
+        };
+        self.robots_cache.lock().await.insert(robots_url, (rules.clone(), Instant::now()));
+        rules
+    }
+
+    /// True when `self.config.respect_robots_txt` is off, or `url`'s `robots.txt` (cached, or fetched on a miss)
+    /// doesn't disallow it for our user agent. A missing/erroring `robots.txt` defaults
+    /// to "allow".
+    async fn is_allowed(&self, url: &str) -> bool {
+        if !self.config.respect_robots_txt {
+            return true;
+        }
+        let Ok(parsed) = Url::parse(url) else { return false };
+        let rules = self.robots_rules_for(&parsed).await;
+        !rules.is_disallowed(parsed.path())
+    }
+
+    /// The `Crawl-delay` (in ms) from `url`'s host's `robots.txt` (cached, or fetched on a
+    /// miss), if any, else `0`.
+    async fn crawl_delay_ms(&self, url: &str) -> u64 {
+        let Ok(parsed) = Url::parse(url) else { return 0 };
+        let rules = self.robots_rules_for(&parsed).await;
+        rules.crawl_delay.map(|secs| (secs * 1000.0) as u64).unwrap_or(0)
+    }
+
+    /// Discovers and fetches every sitemap for `origin` (from its `robots.txt` `Sitemap:`
+    /// directives plus the conventional `/sitemap.xml` fallback), the first time `origin`
+    /// is seen, and returns the crawlable `<loc>` URLs found. Subsequent calls for an
+    /// already-seen origin return an empty list.
+    async fn discover_sitemap_urls(&self, origin: &str) -> Vec<String> {
+        {
+            let mut seen = self.sitemap_origins_seen.lock().await;
+            if !seen.insert(origin.to_string()) {
+                return Vec::new();
+            }
+        }
+
+        let robots_url = format!("{}/robots.txt", origin);
+        let user_agent = self.config.user_agents.first().map(String::as_str).unwrap_or("*");
+        let mut candidates = match self.client.get(&robots_url).send().await {
+            Ok(response) if response.status().is_success() => {
+                let body = response.text().await.unwrap_or_default();
+                RobotsRules::parse(&body, user_agent).sitemaps
+            }
+            _ => Vec::new(),
+        };
+        candidates.push(format!("{}/sitemap.xml", origin));
+
+        let mut locs = Vec::new();
+        let mut fetched = HashSet::new();
+        let mut frontier: Vec<(String, usize)> = candidates.into_iter().map(|c| (c, 0)).collect();
+
+        while let Some((sitemap_url, depth)) = frontier.pop() {
+            if fetched.len() >= MAX_SITEMAPS_FOLLOWED || depth > MAX_SITEMAP_RECURSION_DEPTH {
+                continue;
+            }
+            if !fetched.insert(sitemap_url.clone()) {
+                continue;
+            }
+
+            let Some(body) = self.fetch_sitemap_body(&sitemap_url).await else { continue };
+            let (url_locs, index_locs) = parse_sitemap_xml(&body);
+            locs.extend(url_locs.into_iter().filter(|u| self.is_crawlable_url(u)));
+            frontier.extend(index_locs.into_iter().map(|loc| (loc, depth + 1)));
+        }
+
+        locs
+    }
+
+    /// Downloads a sitemap body, transparently gunzipping `.xml.gz` sitemaps.
+    async fn fetch_sitemap_body(&self, url: &str) -> Option<String> {
+        let response = self.client.get(url).send().await.ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        let bytes = response.bytes().await.ok()?;
+
+        if url.ends_with(".gz") {
+            use std::io::Read;
+            let mut decoder = flate2::read::GzDecoder::new(&bytes[..]);
+            let mut decompressed = String::new();
+            decoder.read_to_string(&mut decompressed).ok()?;
+            Some(decompressed)
+        } else {
+            String::from_utf8(bytes.to_vec()).ok()
+        }
+    }
+
+    /// The delay to enforce before the next request to `url`'s host: whichever is larger
+    /// of `max_requests_per_host_per_second`'s implied spacing and the host's robots.txt
+    /// `Crawl-delay`.
+    async fn effective_delay_ms(&self, url: &str) -> u64 {
+        let rate_delay_ms = if self.config.max_requests_per_host_per_second > 0.0 {
+            (1000.0 / self.config.max_requests_per_host_per_second) as u64
+        } else {
+            0
+        };
+        rate_delay_ms.max(self.crawl_delay_ms(url).await)
+    }
+
+    /// Blocks until at least `effective_delay_ms` has elapsed since the last request to
+    /// `url`'s host, so concurrent tasks never hammer one host even when the frontier as
+    /// a whole has many other hosts ready to go.
+    async fn wait_for_host_slot(&self, url: &str) {
+        let Some(host) = Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string)) else {
+            return;
+        };
+        let delay = Duration::from_millis(self.effective_delay_ms(url).await);
+        if delay.is_zero() {
+            return;
+        }
+
+        loop {
+            let now = Instant::now();
+            let wait = {
+                let mut gate = self.host_gate.lock().await;
+                let next_allowed = gate.get(&host).copied().unwrap_or(now);
+                if now >= next_allowed {
+                    gate.insert(host.clone(), now + delay);
+                    None
+                } else {
+                    Some(next_allowed - now)
+                }
+            };
+
+            match wait {
+                None => return,
+                Some(remaining) => sleep(remaining).await,
+            }
+        }
+    }
+
     pub async fn crawl_for_duration(&self, duration_hours: u64) -> Result<usize> {
         info!("Starting crawling for {} hours", duration_hours);
+
+        // Clear out already-queued/crawled URLs that now fall under the weed list before
+        // spending any time on this session.
+        if !self.config.weed_domains.is_empty() {
+            match self.database.prune_weeded_seed_urls(&self.config.weed_domains).await {
+                Ok(removed) if removed > 0 => info!("Pruned {} weeded seed URLs", removed),
+                Ok(_) => {}
+                Err(e) => warn!("Failed to prune weeded seed URLs: {}", e),
+            }
+        }
+
         let start_time = Instant::now();
         let duration = Duration::from_secs(duration_hours * 3600);
 
+        // `page_budget`/`max_level` are additional, optional caps layered on top of the
+        // duration-based stop condition and `max_depth`.
+        let effective_max_pages = self.config.page_budget.unwrap_or(usize::MAX);
+        let effective_max_depth = self.config.max_level
+            .map_or(self.config.max_depth as usize, |l| l.min(self.config.max_depth as usize));
+        let links_per_page_budget = self.config.links_per_page_budget.unwrap_or(5);
+
         let mut total_crawled = 0;
-        let mut current_urls = Vec::new();
+        // (url, depth) — seeds and sitemap-discovered URLs start at depth 0.
+        let mut current_urls: Vec<(String, usize)> = Vec::new();
 
-        while start_time.elapsed() < duration {
+        while start_time.elapsed() < duration && total_crawled < effective_max_pages {
             // Get seed URLs if we don't have any current URLs
             if current_urls.is_empty() {
                 let seed_urls = self.database.get_seed_urls(20).await?;
-                current_urls = seed_urls.into_iter().map(|s| s.url).collect();
+                current_urls = seed_urls.into_iter().map(|s| (s.url, 0)).collect();
 
                 if current_urls.is_empty() {
                     warn!("No seed URLs available for crawling");
                     sleep(Duration::from_secs(60)).await;
                     continue;
                 }
+
+                // Discover sitemaps for any newly-seen origin among this batch of seeds and
+                // feed their entries into the frontier alongside the seeds themselves.
+                let mut seed_origins = HashSet::new();
+                for (seed, _) in &current_urls {
+                    if let Ok(parsed) = Url::parse(seed) {
+                        seed_origins.insert(parsed.origin().unicode_serialization());
+                    }
+                }
+                let mut sitemap_urls = Vec::new();
+                for origin in &seed_origins {
+                    sitemap_urls.extend(self.discover_sitemap_urls(origin).await);
+                }
+                if !sitemap_urls.is_empty() {
+                    info!("Discovered {} sitemap URLs for {} origin(s)", sitemap_urls.len(), seed_origins.len());
+                    if let Err(e) = self.database.add_seed_urls(&sitemap_urls).await {
+                        warn!("Failed to add sitemap-discovered seed URLs: {}", e);
+                    }
+                    current_urls.extend(sitemap_urls.into_iter().map(|url| (url, 0)));
+                }
             }
 
-            // Process URLs in batches
-            let batch_size = self.config.max_concurrent_requests.min(current_urls.len());
+            // Process URLs in batches, never fetching past the page budget.
+            let remaining_budget = effective_max_pages.saturating_sub(total_crawled);
+            let batch_size = self.config.max_concurrent_requests.min(current_urls.len()).min(remaining_budget);
             let batch: Vec<_> = current_urls.drain(..batch_size).collect();
 
-            let tasks: Vec<_> = batch.into_iter().map(|url| {
+            let tasks: Vec<_> = batch.iter().map(|(url, _)| {
                 let crawler = self.clone();
+                let url = url.clone();
                 async move {
                     crawler.crawl_url(&url).await
                 }
@@ -75,7 +410,7 @@ impl Crawler {
 
             let results = join_all(tasks).await;
 
-            for result in results {
+            for ((_, depth), result) in batch.into_iter().zip(results.into_iter()) {
                 match result {
                     Ok((crawl_result, discovered_urls)) => {
                         total_crawled += 1;
@@ -90,17 +425,23 @@ impl Crawler {
                             warn!("Failed to update seed URL status: {}", e);
                         }
 
-                        // Add discovered URLs to our queue (limit to prevent explosion)
-                        let filtered_urls: Vec<_> = discovered_urls
-                            .into_iter()
-                            .filter(|url| self.is_crawlable_url(url))
-                            .take(5) // Limit per page
-                            .collect();
+                        // Add discovered URLs to our queue (limit to prevent explosion), as
+                        // long as doing so wouldn't exceed `max_level`/`max_depth`.
+                        let next_depth = depth + 1;
+                        if next_depth <= effective_max_depth {
+                            let filtered_urls: Vec<_> = discovered_urls
+                                .into_iter()
+                                .filter(|url| self.is_crawlable_url(url))
+                                .take(links_per_page_budget)
+                                .map(|url| (url, next_depth))
+                                .collect();
 
-                        current_urls.extend(filtered_urls);
+                            current_urls.extend(filtered_urls);
+                        }
 
                         // Also add unique domains to seed URLs
-                        if let Ok(unique_urls) = self.extract_unique_domains(&current_urls).await {
+                        let frontier_urls: Vec<String> = current_urls.iter().map(|(url, _)| url.clone()).collect();
+                        if let Ok(unique_urls) = self.extract_unique_domains(&frontier_urls).await {
                             if !unique_urls.is_empty() {
                                 if let Err(e) = self.database.add_seed_urls(&unique_urls).await {
                                     warn!("Failed to add new seed URLs: {}", e);
@@ -117,7 +458,7 @@ impl Crawler {
             info!("Crawled {} URLs so far", total_crawled);
 
             // Check if we should continue
-            if start_time.elapsed() >= duration {
+            if start_time.elapsed() >= duration || total_crawled >= effective_max_pages {
                 break;
             }
 
@@ -129,6 +470,46 @@ impl Crawler {
         Ok(total_crawled)
     }
 
+    /// Builds a `CrawlResult` for a fetch that didn't produce a page: an HTTP error, a
+    /// timed-out fetch, or a body that was aborted for exceeding `max_body_bytes`.
+    fn failed_result(
+        &self,
+        url: &str,
+        http_status_code: Option<i32>,
+        response_time_ms: Option<i32>,
+        error_message: String,
+    ) -> CrawlResult {
+        CrawlResult {
+            url: url.to_string(),
+            original_url: Some(url.to_string()),
+            redirect_chain: None,
+            title: None,
+            meta_description: None,
+            content_text: None,
+            content_html: None,
+            content_hash: None,
+            word_count: None,
+            page_size: None,
+            http_status_code,
+            response_time_ms,
+            language: None,
+            charset: None,
+            h1_tags: None,
+            h2_tags: None,
+            meta_keywords: None,
+            canonical_url: None,
+            robots_meta: None,
+            internal_links_count: None,
+            external_links_count: None,
+            images_count: None,
+            content_type: None,
+            file_extension: None,
+            crawl_success: false,
+            error_message: Some(error_message),
+            crawled_at: Utc::now(),
+        }
+    }
+
     async fn crawl_url(&self, url: &str) -> Result<(CrawlResult, Vec<String>)> {
         // Check if already visited
         {
@@ -138,6 +519,10 @@ impl Crawler {
             }
         }
 
+        if !self.is_allowed(url).await {
+            return Err(anyhow::anyhow!("Disallowed by robots.txt: {}", url));
+        }
+
         // Get permit for concurrent request
         let _permit = self.semaphore.acquire().await?;
 
@@ -147,6 +532,11 @@ impl Crawler {
             visited.insert(url.to_string());
         }
 
+        // Never exceed max_requests_per_host_per_second (or the host's robots.txt
+        // Crawl-delay, if stricter) for this host, regardless of how many other tasks are
+        // concurrently crawling other hosts.
+        self.wait_for_host_slot(url).await;
+
         let start_time = Instant::now();
 
         // Select random user agent
@@ -154,93 +544,183 @@ impl Crawler {
             .choose(&mut rand::thread_rng())
             .unwrap_or(&self.config.user_agents[0]);
 
-        let response = self.client
-            .get(url)
-            .header("User-Agent", user_agent)
-            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
-            .header("Accept-Language", "en-US,en;q=0.5")
-            .header("Accept-Encoding", "gzip, deflate, br")
-            .header("DNT", "1")
-            .header("Connection", "keep-alive")
-            .header("Upgrade-Insecure-Requests", "1")
-            .send()
-            .await?;
+        // Follow redirects manually (the client is built with `redirect::Policy::none()`)
+        // so every hop is recorded, loops are caught, and `max_redirects` is enforced. The
+        // whole fetch, redirects and body included, is bounded by `max_fetch_duration_secs`
+        // so a slow or stalled server can't pin a worker down indefinitely, and the body is
+        // streamed in rather than buffered in one shot so it can be aborted the moment it
+        // crosses `max_body_bytes`.
+        let fetch = async {
+            let mut current_url = url.to_string();
+            let mut redirect_chain = Vec::new();
+            let mut hops_seen: HashSet<String> = HashSet::new();
+            hops_seen.insert(current_url.clone());
 
+            let response = loop {
+                let response = self.client
+                    .get(&current_url)
+                    .header("User-Agent", user_agent)
+                    .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
+                    .header("Accept-Language", "en-US,en;q=0.5")
+                    .header("Accept-Encoding", "gzip, deflate, br")
+                    .header("DNT", "1")
+                    .header("Connection", "keep-alive")
+                    .header("Upgrade-Insecure-Requests", "1")
+                    .send()
+                    .await?;
+
+                let status = response.status();
+                if !status.is_redirection() {
+                    break response;
+                }
+
+                let location = response.headers()
+                    .get(reqwest::header::LOCATION)
+                    .and_then(|v| v.to_str().ok())
+                    .ok_or_else(|| anyhow::anyhow!("redirect response from {} missing Location header", current_url))?
+                    .to_string();
+                let next_url = Url::parse(&current_url).and_then(|base| base.join(&location))?.to_string();
+
+                redirect_chain.push(format!("{} {}", status.as_u16(), current_url));
+                if redirect_chain.len() > self.config.max_redirects {
+                    return Err(anyhow::anyhow!("exceeded max_redirects ({}) fetching {}", self.config.max_redirects, url));
+                }
+                if !hops_seen.insert(next_url.clone()) {
+                    return Err(anyhow::anyhow!("redirect loop detected at {} fetching {}", next_url, url));
+                }
+
+                current_url = next_url;
+            };
+
+            let status_code = response.status().as_u16();
+            let final_url = response.url().to_string();
+            let x_robots_tag = response.headers()
+                .get("x-robots-tag")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string();
+            let content_type_header = response.headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string();
+            let is_success = response.status().is_success();
+
+            // Reject disallowed content types after headers arrive, before the body streams.
+            let base_content_type = content_type_header.split(';').next().unwrap_or("").trim().to_string();
+            if is_success && !self.config.accepted_content_types.is_empty() && !base_content_type.is_empty()
+                && !self.config.accepted_content_types.iter().any(|t| t.eq_ignore_ascii_case(&base_content_type))
+            {
+                return Err(anyhow::anyhow!(
+                    "rejected content-type {} for {}", content_type_header, final_url
+                ));
+            }
+
+            let mut body = Vec::new();
+            let mut stream = response.bytes_stream();
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk?;
+                body.extend_from_slice(&chunk);
+                if body.len() > self.config.max_body_bytes {
+                    return Err(anyhow::anyhow!(
+                        "response body for {} exceeded max_body_bytes ({})",
+                        final_url, self.config.max_body_bytes
+                    ));
+                }
+            }
+
+            Ok((status_code, final_url, redirect_chain, x_robots_tag, content_type_header, is_success, body))
+        };
+
+        let fetch_outcome = tokio::time::timeout(
+            Duration::from_secs(self.config.max_fetch_duration_secs),
+            fetch,
+        ).await;
         let response_time_ms = start_time.elapsed().as_millis() as i32;
-        let status_code = response.status().as_u16() as i32;
-        let final_url = response.url().to_string();
 
-        if !response.status().is_success() {
-            return Ok((CrawlResult {
-                url: url.to_string(),
-                original_url: Some(final_url),
-                redirect_chain: None,
-                title: None,
-                meta_description: None,
-                content_text: None,
-                content_html: None,
-                content_hash: None,
-                word_count: None,
-                page_size: None,
-                http_status_code: Some(status_code),
-                response_time_ms: Some(response_time_ms),
-                language: None,
-                charset: None,
-                h1_tags: None,
-                h2_tags: None,
-                meta_keywords: None,
-                canonical_url: None,
-                robots_meta: None,
-                internal_links_count: None,
-                external_links_count: None,
-                images_count: None,
-                content_type: None,
-                file_extension: None,
-                crawl_success: false,
-                error_message: Some(format!("HTTP error: {}", status_code)),
-                crawled_at: Utc::now(),
-            }, Vec::new()));
+        let (status_code, final_url, redirect_chain, x_robots_tag, content_type_header, is_success, body) = match fetch_outcome {
+            Ok(Ok(data)) => data,
+            Ok(Err(e)) => {
+                return Ok((self.failed_result(url, None, Some(response_time_ms), e.to_string()), Vec::new()));
+            }
+            Err(_) => {
+                return Ok((self.failed_result(
+                    url,
+                    None,
+                    Some(response_time_ms),
+                    format!("fetch of {} exceeded max_fetch_duration_secs ({}s)", url, self.config.max_fetch_duration_secs),
+                ), Vec::new()));
+            }
+        };
+        let status_code = status_code as i32;
+
+        if !is_success {
+            let mut result = self.failed_result(
+                &final_url,
+                Some(status_code),
+                Some(response_time_ms),
+                format!("HTTP error: {}", status_code),
+            );
+            result.redirect_chain = if redirect_chain.is_empty() { None } else { Some(redirect_chain) };
+            return Ok((result, Vec::new()));
         }
 
-        let body = response.text().await?;
+        let body = String::from_utf8_lossy(&body).into_owned();
         let document = Html::parse_document(&body);
 
         // Extract page information
         let title = self.extract_title(&document);
         let meta_description = self.extract_meta_description(&document);
-        let content_text = self.extract_text_content(&document);
+        let (cleaned_html, content_text, word_count) = self.sanitize_html(&body);
         let h1_tags = self.extract_headings(&document, "h1");
         let h2_tags = self.extract_headings(&document, "h2");
         let meta_keywords = self.extract_meta_keywords(&document);
         let canonical_url = self.extract_canonical_url(&document);
         let robots_meta = self.extract_robots_meta(&document);
-        let discovered_links = self.extract_links(&document, url);
+        let directives = RobotsDirectives::parse(&format!("{},{}", robots_meta, x_robots_tag));
+
+        // nofollow: don't spend the frontier on this page's outbound links at all.
+        let discovered_links = if directives.nofollow {
+            Vec::new()
+        } else {
+            self.extract_links(&document, url)
+        };
 
         // Calculate metrics
-        let word_count = content_text.split_whitespace().count() as i32;
+        let word_count = word_count as i32;
         let page_size = body.len() as i32;
         let content_hash = format!("{:x}", Sha256::digest(body.as_bytes()));
 
+        let language = self.detect_language(&content_text);
+        let charset = self.detect_charset(&content_type_header, &document);
+
         // Count links and images
         let internal_links_count = self.count_internal_links(&discovered_links, url);
         let external_links_count = discovered_links.len() as i32 - internal_links_count;
         let images_count = self.count_images(&document);
 
+        // noindex: keep this a successful crawl, but don't persist the page content.
+        let (content_text, content_html) = if directives.noindex {
+            (None, None)
+        } else {
+            (Some(content_text), Some(cleaned_html))
+        };
+
         let crawl_result = CrawlResult {
-            url: url.to_string(),
-            original_url: if final_url != url { Some(final_url) } else { None },
-            redirect_chain: None, // TODO: Track redirect chain
+            url: final_url,
+            original_url: Some(url.to_string()),
+            redirect_chain: if redirect_chain.is_empty() { None } else { Some(redirect_chain) },
             title: Some(title),
             meta_description: Some(meta_description),
-            content_text: Some(content_text),
-            content_html: Some(body),
+            content_text,
+            content_html,
             content_hash: Some(content_hash),
             word_count: Some(word_count),
             page_size: Some(page_size),
             http_status_code: Some(status_code),
             response_time_ms: Some(response_time_ms),
-            language: None, // TODO: Detect language
-            charset: None,  // TODO: Extract charset
+            language,
+            charset: Some(charset),
             h1_tags: Some(h1_tags),
             h2_tags: Some(h2_tags),
             meta_keywords: Some(meta_keywords),
@@ -256,9 +736,6 @@ impl Crawler {
             crawled_at: Utc::now(),
         };
 
-        // Respect delay
-        sleep(Duration::from_millis(self.config.delay_between_requests_ms)).await;
-
         Ok((crawl_result, discovered_links))
     }
 
@@ -284,20 +761,39 @@ impl Crawler {
             .to_string()
     }
 
-    fn extract_text_content(&self, document: &Html) -> String {
-        // Remove script and style elements first
-        let mut text_parts = Vec::new();
+    /// Cleans raw page `html` through `self.sanitize_policy`'s tag/attribute allowlist
+    /// (stripping `<script>`/`<style>`/`<noscript>` and event-handler attributes) and
+    /// derives whitespace-normalized body text and a word count from the cleaned DOM, so
+    /// neither inflates on script/style contents the way raw `body.text()` would.
+    fn sanitize_html(&self, html: &str) -> (String, String, usize) {
+        let mut builder = ammonia::Builder::default();
+        let tags: HashSet<&str> = self
+            .sanitize_policy
+            .allowed_tags
+            .iter()
+            .map(String::as_str)
+            .collect();
+        builder.tags(tags);
 
-        let body_selector = Selector::parse("body").unwrap();
-        if let Some(body) = document.select(&body_selector).next() {
-            let text: String = body.text().collect::<Vec<_>>().join(" ");
-            text_parts.push(text);
+        let mut attributes: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for (tag, attrs) in &self.sanitize_policy.allowed_attributes {
+            attributes.insert(tag.as_str(), attrs.iter().map(String::as_str).collect());
         }
+        builder.tag_attributes(attributes);
+
+        let cleaned_html = builder.clean(html).to_string();
 
-        text_parts.join(" ")
-            .split_whitespace()
-            .collect::<Vec<_>>()
-            .join(" ")
+        let document = Html::parse_fragment(&cleaned_html);
+        let body_selector = Selector::parse("body").unwrap();
+        let text = document
+            .select(&body_selector)
+            .next()
+            .map(|el| el.text().collect::<Vec<_>>().join(" "))
+            .unwrap_or_else(|| document.root_element().text().collect::<Vec<_>>().join(" "));
+        let normalized_text: String = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let word_count = normalized_text.split_whitespace().count();
+
+        (cleaned_html, normalized_text, word_count)
     }
 
     fn extract_headings(&self, document: &Html, tag: &str) -> Vec<String> {
@@ -342,6 +838,46 @@ impl Crawler {
             .to_string()
     }
 
+    /// Runs `content_text` through `whatlang`'s n-gram classifier, returning the detected
+    /// ISO language code only if there was enough text to classify and the confidence
+    /// clears `config.min_language_confidence`.
+    fn detect_language(&self, content_text: &str) -> Option<String> {
+        if content_text.trim().chars().count() < MIN_LANGUAGE_DETECTION_CHARS {
+            return None;
+        }
+
+        whatlang::detect(content_text).and_then(|info| {
+            if info.confidence() as f32 >= self.config.min_language_confidence {
+                Some(info.lang().code().to_string())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Derives the page's charset from the `Content-Type` header, falling back to a
+    /// `<meta charset>` tag and finally UTF-8 if neither is present.
+    fn detect_charset(&self, content_type_header: &str, document: &Html) -> String {
+        if let Some(charset) = content_type_header
+            .split(';')
+            .find(|s| s.trim().to_ascii_lowercase().starts_with("charset="))
+            .map(|s| s.trim()[8..].trim().to_string())
+        {
+            return charset;
+        }
+
+        let meta_charset_selector = Selector::parse("meta[charset]").unwrap();
+        if let Some(charset) = document
+            .select(&meta_charset_selector)
+            .next()
+            .and_then(|el| el.value().attr("charset"))
+        {
+            return charset.trim().to_string();
+        }
+
+        "utf-8".to_string()
+    }
+
     fn extract_links(&self, document: &Html, base_url: &str) -> Vec<String> {
         let link_selector = Selector::parse("a[href]").unwrap();
         let mut links = Vec::new();
@@ -390,6 +926,21 @@ impl Crawler {
                 return false;
             }
 
+            // Respect allowed_domains/weed_domains: a weeded host is always rejected, and
+            // when allowed_domains is non-empty only matching hosts pass.
+            if let Some(host) = parsed_url.host_str() {
+                if self.config.weed_domains.iter().any(|d| host_matches(host, d)) {
+                    return false;
+                }
+                if !self.config.allowed_domains.is_empty()
+                    && !self.config.allowed_domains.iter().any(|d| host_matches(host, d))
+                {
+                    return false;
+                }
+            } else {
+                return false;
+            }
+
             // Avoid certain file extensions
             if let Some(path) = parsed_url.path_segments() {
                 if let Some(last_segment) = path.last() {
@@ -448,6 +999,65 @@ impl Clone for Crawler {
             config: self.config.clone(),
             semaphore: self.semaphore.clone(),
             visited_urls: self.visited_urls.clone(),
+            robots_cache: self.robots_cache.clone(),
+            host_gate: self.host_gate.clone(),
+            sitemap_origins_seen: self.sitemap_origins_seen.clone(),
+            sanitize_policy: self.sanitize_policy.clone(),
+        }
+    }
+}
+
+/// Parses an XML sitemap or sitemap-index body, returning the `<url><loc>` URLs found
+/// plus any `<sitemap><loc>` entries to recurse into.
+fn parse_sitemap_xml(xml: &str) -> (Vec<String>, Vec<String>) {
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    let mut urls = Vec::new();
+    let mut index_locs = Vec::new();
+    let mut in_sitemap_index_entry = false;
+    let mut current_tag = String::new();
+    let mut loc: Option<String> = None;
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                match name.as_str() {
+                    "sitemap" => in_sitemap_index_entry = true,
+                    "url" => in_sitemap_index_entry = false,
+                    _ => {}
+                }
+                current_tag = name;
+            }
+            Ok(Event::Text(text)) => {
+                if current_tag == "loc" {
+                    loc = text.unescape().map(|s| s.to_string()).ok();
+                }
+            }
+            Ok(Event::End(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                if name == "sitemap" || name == "url" {
+                    if let Some(loc_value) = loc.take() {
+                        if in_sitemap_index_entry {
+                            index_locs.push(loc_value);
+                        } else {
+                            urls.push(loc_value);
+                        }
+                    }
+                }
+                current_tag.clear();
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => break,
+            _ => {}
         }
+        buf.clear();
     }
+
+    (urls, index_locs)
 }
@@ -2,6 +2,7 @@ use crate::database::Database;
 use crate::models::{DashboardStats, SystemHealth};
 use anyhow::Result;
 use log::{info, error};
+use serde::Deserialize;
 use serde_json::json;
 use std::convert::Infallible;
 use std::sync::Arc;
@@ -43,6 +44,13 @@ impl DashboardServer {
             .and(with_db(db.clone()))
             .and_then(get_recent_crawls);
 
+        // API route for recently changed pages (recrawls whose content_hash differed)
+        let changes_route = warp::path("api")
+            .and(warp::path("recent-changes"))
+            .and(warp::path::end())
+            .and(with_db(db.clone()))
+            .and_then(get_recent_changes);
+
         // Static files route
         let static_route = warp::path("static")
             .and(warp::fs::dir("static"));
@@ -57,11 +65,28 @@ impl DashboardServer {
                 }))
             });
 
+        // Prometheus scrape endpoint
+        let metrics_route = warp::path("metrics")
+            .and(warp::path::end())
+            .and(with_db(db.clone()))
+            .and_then(get_metrics);
+
+        // API route for past processing-session history
+        let sessions_route = warp::path("api")
+            .and(warp::path("sessions"))
+            .and(warp::path::end())
+            .and(warp::query::<SessionsQuery>())
+            .and(with_db(db.clone()))
+            .and_then(get_processing_sessions);
+
         let routes = dashboard_route
             .or(stats_route)
             .or(crawls_route)
+            .or(changes_route)
             .or(static_route)
             .or(health_route)
+            .or(metrics_route)
+            .or(sessions_route)
             .with(warp::cors().allow_any_origin());
 
         warp::serve(routes)
@@ -108,3 +133,101 @@ async fn get_recent_crawls(db: Arc<Database>) -> Result<impl Reply, Infallible>
         }
     }
 }
+
+async fn get_recent_changes(db: Arc<Database>) -> Result<impl Reply, Infallible> {
+    match db.get_recent_changes(50).await {
+        Ok(changes) => Ok(warp::reply::json(&changes)),
+        Err(e) => {
+            error!("Failed to get recent changes: {}", e);
+            Ok(warp::reply::json(&json!({
+                "error": "Failed to get recent changes"
+            })))
+        }
+    }
+}
+
+/// Renders `DashboardStats` (plus the error count) as Prometheus text exposition format so
+/// RatCrawler can be scraped like any other service.
+async fn get_metrics(db: Arc<Database>) -> Result<impl Reply, Infallible> {
+    let stats = match db.get_dashboard_stats().await {
+        Ok(stats) => stats,
+        Err(e) => {
+            error!("Failed to get dashboard stats for /metrics: {}", e);
+            return Ok(warp::reply::with_header(
+                "# failed to collect ratcrawler metrics\n".to_string(),
+                "Content-Type",
+                "text/plain; version=0.0.4",
+            ));
+        }
+    };
+    let error_count = db.get_error_count().await.unwrap_or(0);
+
+    let mode_label = |mode: &str| if mode == "backlink_processing" { 1 } else { 0 };
+
+    let body = format!(
+        "# HELP ratcrawler_pages_crawled_total Total successfully crawled pages.\n\
+         # TYPE ratcrawler_pages_crawled_total counter\n\
+         ratcrawler_pages_crawled_total {}\n\
+         # HELP ratcrawler_backlinks_found_total Total backlinks discovered.\n\
+         # TYPE ratcrawler_backlinks_found_total counter\n\
+         ratcrawler_backlinks_found_total {}\n\
+         # HELP ratcrawler_errors_total Total failed crawl attempts.\n\
+         # TYPE ratcrawler_errors_total counter\n\
+         ratcrawler_errors_total {}\n\
+         # HELP ratcrawler_unique_domains Unique domains seen across crawled pages.\n\
+         # TYPE ratcrawler_unique_domains gauge\n\
+         ratcrawler_unique_domains {}\n\
+         # HELP ratcrawler_crawl_rate_per_hour Pages crawled in the last hour.\n\
+         # TYPE ratcrawler_crawl_rate_per_hour gauge\n\
+         ratcrawler_crawl_rate_per_hour {}\n\
+         # HELP ratcrawler_backlink_rate_per_hour Backlinks discovered in the last hour.\n\
+         # TYPE ratcrawler_backlink_rate_per_hour gauge\n\
+         ratcrawler_backlink_rate_per_hour {}\n\
+         # HELP ratcrawler_database_size_mb Database size in megabytes.\n\
+         # TYPE ratcrawler_database_size_mb gauge\n\
+         ratcrawler_database_size_mb {}\n\
+         # HELP ratcrawler_system_memory_usage_percent Process host memory usage percent.\n\
+         # TYPE ratcrawler_system_memory_usage_percent gauge\n\
+         ratcrawler_system_memory_usage_percent {}\n\
+         # HELP ratcrawler_system_cpu_usage_percent Process host CPU usage percent.\n\
+         # TYPE ratcrawler_system_cpu_usage_percent gauge\n\
+         ratcrawler_system_cpu_usage_percent {}\n\
+         # HELP ratcrawler_backlink_processing_mode 1 when currently in backlink discovery mode, 0 when crawling.\n\
+         # TYPE ratcrawler_backlink_processing_mode gauge\n\
+         ratcrawler_backlink_processing_mode {}\n",
+        stats.total_urls_crawled,
+        stats.total_backlinks_found,
+        error_count,
+        stats.unique_domains,
+        stats.crawl_rate_per_hour,
+        stats.backlink_rate_per_hour,
+        stats.database_size_mb,
+        stats.system_memory_usage,
+        stats.system_cpu_usage,
+        mode_label(&stats.current_mode),
+    );
+
+    Ok(warp::reply::with_header(body, "Content-Type", "text/plain; version=0.0.4"))
+}
+
+#[derive(Deserialize)]
+struct SessionsQuery {
+    session_type: Option<String>,
+    status: Option<String>,
+    limit: Option<i32>,
+    offset: Option<i32>,
+}
+
+async fn get_processing_sessions(query: SessionsQuery, db: Arc<Database>) -> Result<impl Reply, Infallible> {
+    let limit = query.limit.unwrap_or(50);
+    let offset = query.offset.unwrap_or(0);
+    match db.list_processing_sessions(query.session_type, query.status, limit, offset).await {
+        Ok(sessions) => Ok(warp::reply::json(&sessions)),
+        Err(e) => {
+            error!("Failed to list processing sessions: {}", e);
+            Ok(warp::reply::json(&json!({
+                "error": "Failed to list processing sessions"
+            })))
+        }
+    }
+}
@@ -1,25 +1,149 @@
+use r2d2::{CustomizeConnection, Pool, PooledConnection};
+use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::{Connection, Result, params, OptionalExtension};
 use std::collections::HashMap;
+use std::time::Duration;
 use chrono::{DateTime, Utc};
 use crate::models::*;
 use serde_json;
 
+const DEFAULT_POOL_MAX_SIZE: u32 = 8;
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+const INITIAL_RECRAWL_INTERVAL_SECS: i64 = 24 * 60 * 60;
+const MIN_RECRAWL_INTERVAL_SECS: i64 = 60 * 60;
+const MAX_RECRAWL_INTERVAL_SECS: i64 = 30 * 24 * 60 * 60;
+const RECRAWL_BACKOFF_FACTOR: f64 = 2.0;
+const RECRAWL_SHRINK_FACTOR: f64 = 0.5;
+
+/// True when `host` is (or is a subdomain of) `domain`, used to match stored URLs against
+/// a weed list for retroactive pruning.
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+#[derive(Debug)]
+struct ConnectionCustomizer;
+
+impl CustomizeConnection<Connection, rusqlite::Error> for ConnectionCustomizer {
+    fn on_acquire(&self, conn: &mut Connection) -> std::result::Result<(), rusqlite::Error> {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        Ok(())
+    }
+}
+
+/// Like `ConnectionCustomizer`, but skips the `journal_mode`/`synchronous` pragmas that
+/// would fail (or silently no-op) against a `SQLITE_OPEN_READ_ONLY` connection.
 #[derive(Debug)]
+struct ReadOnlyConnectionCustomizer;
+
+impl CustomizeConnection<Connection, rusqlite::Error> for ReadOnlyConnectionCustomizer {
+    fn on_acquire(&self, conn: &mut Connection) -> std::result::Result<(), rusqlite::Error> {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        Ok(())
+    }
+}
+
+/// Computes a 64-bit SimHash fingerprint over word 3-gram shingles of `text`. Each
+/// shingle is hashed to 64 bits and votes +1/-1 (weighted by how often it occurs) on
+/// every bit position; the sign of each column becomes the final fingerprint bit. This
+/// catches near-duplicate pages (boilerplate differing only by a timestamp or nav tweak)
+/// that an exact `content_hash` comparison would miss.
+fn simhash(text: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let words: Vec<String> = text.split_whitespace().map(|w| w.to_ascii_lowercase()).collect();
+    let mut shingle_counts: HashMap<String, u32> = HashMap::new();
+    for shingle in words.windows(3) {
+        *shingle_counts.entry(shingle.join(" ")).or_insert(0) += 1;
+    }
+    // Fall back to single words for very short pages that don't yield any 3-grams.
+    if shingle_counts.is_empty() {
+        for word in &words {
+            *shingle_counts.entry(word.clone()).or_insert(0) += 1;
+        }
+    }
+
+    let mut bit_votes = [0i64; 64];
+    for (shingle, count) in &shingle_counts {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        shingle.hash(&mut hasher);
+        let shingle_hash = hasher.finish();
+
+        for (bit, vote) in bit_votes.iter_mut().enumerate() {
+            if shingle_hash & (1u64 << bit) != 0 {
+                *vote += *count as i64;
+            } else {
+                *vote -= *count as i64;
+            }
+        }
+    }
+
+    let mut fingerprint: u64 = 0;
+    for (bit, vote) in bit_votes.iter().enumerate() {
+        if *vote > 0 {
+            fingerprint |= 1u64 << bit;
+        }
+    }
+    fingerprint
+}
+
+fn new_pool(db_path: &str) -> Result<Pool<SqliteConnectionManager>> {
+    let manager = SqliteConnectionManager::file(db_path);
+    Pool::builder()
+        .max_size(DEFAULT_POOL_MAX_SIZE)
+        .connection_customizer(Box::new(ConnectionCustomizer))
+        .build(manager)
+        .map_err(|e| rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+            Some(e.to_string()),
+        ))
+}
+
+#[derive(Debug, Clone)]
 pub struct WebsiteCrawlerDatabase {
-    conn: Connection,
+    pool: Pool<SqliteConnectionManager>,
+    read_only: bool,
 }
 
 impl WebsiteCrawlerDatabase {
     pub fn new(db_path: &str) -> Result<Self> {
-        let conn = Connection::open(db_path)?;
-        let mut db = Self { conn };
+        let pool = new_pool(db_path)?;
+        let db = Self { pool, read_only: false };
         db.init_database()?;
         Ok(db)
     }
 
-    fn init_database(&mut self) -> Result<()> {
+    /// Opens an existing database for ad-hoc analysis with the connection itself set to
+    /// `SQLITE_OPEN_READ_ONLY`, so a mistyped `query()` call cannot mutate production data.
+    /// Assumes the schema already exists; does not run `init_database`.
+    pub fn new_read_only(db_path: &str) -> Result<Self> {
+        let manager = SqliteConnectionManager::file(db_path)
+            .with_flags(rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY);
+        let pool = Pool::builder()
+            .max_size(DEFAULT_POOL_MAX_SIZE)
+            .connection_customizer(Box::new(ReadOnlyConnectionCustomizer))
+            .build(manager)
+            .map_err(|e| rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+                Some(e.to_string()),
+            ))?;
+        Ok(Self { pool, read_only: true })
+    }
+
+    fn conn(&self) -> Result<PooledConnection<SqliteConnectionManager>> {
+        self.pool.get().map_err(|e| rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            Some(e.to_string()),
+        ))
+    }
+
+    fn init_database(&self) -> Result<()> {
+        let conn = self.conn()?;
         // Crawl sessions table
-        self.conn.execute(
+        conn.execute(
             "CREATE TABLE IF NOT EXISTS crawl_sessions (
                 id TEXT PRIMARY KEY,
                 start_time TEXT NOT NULL,
@@ -32,7 +156,7 @@ impl WebsiteCrawlerDatabase {
         )?;
 
         // Crawled pages table
-        self.conn.execute(
+        conn.execute(
             "CREATE TABLE IF NOT EXISTS crawled_pages (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 session_id TEXT,
@@ -49,6 +173,7 @@ impl WebsiteCrawlerDatabase {
                 http_status_code INTEGER,
                 response_time_ms INTEGER,
                 language TEXT,
+                language_confidence REAL,
                 charset TEXT,
                 h1_tags TEXT,
                 h2_tags TEXT,
@@ -59,13 +184,16 @@ impl WebsiteCrawlerDatabase {
                 external_links_count INTEGER,
                 images_count INTEGER,
                 crawl_time TEXT,
+                simhash INTEGER,
+                etag TEXT,
+                last_modified TEXT,
                 FOREIGN KEY(session_id) REFERENCES crawl_sessions(id)
             )",
             [],
         )?;
 
         // Crawl errors table
-        self.conn.execute(
+        conn.execute(
             "CREATE TABLE IF NOT EXISTS crawl_errors (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 session_id TEXT,
@@ -79,17 +207,34 @@ impl WebsiteCrawlerDatabase {
             [],
         )?;
 
+        // Adaptive recrawl schedule: one row per URL, tracking how often its content
+        // actually changes so stable pages get revisited less often than volatile ones.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS recrawl_schedule (
+                url TEXT PRIMARY KEY,
+                content_hash TEXT,
+                interval_secs INTEGER NOT NULL,
+                last_crawl_time TEXT NOT NULL,
+                next_due_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_recrawl_schedule_next_due ON recrawl_schedule(next_due_at)",
+            [],
+        )?;
+
         Ok(())
     }
 
-    pub fn create_crawl_session(&mut self, seed_urls: &[String], config: &serde_json::Value) -> Result<String> {
+    pub fn create_crawl_session(&self, seed_urls: &[String], config: &serde_json::Value) -> Result<String> {
         let session = CrawlSession::new(seed_urls.to_vec(), config.clone());
         let seed_urls_json = serde_json::to_string(&session.seed_urls)
             .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
         let config_json = serde_json::to_string(&session.config)
             .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
 
-        self.conn.execute(
+        self.conn()?.execute(
             "INSERT INTO crawl_sessions (id, start_time, seed_urls, config, status)
              VALUES (?, ?, ?, ?, ?)",
             params![
@@ -104,22 +249,23 @@ impl WebsiteCrawlerDatabase {
         Ok(session.id)
     }
 
-    pub fn store_crawled_page(&mut self, page: &CrawledPage, session_id: &str) -> Result<()> {
+    pub fn store_crawled_page(&self, page: &CrawledPage, session_id: &str) -> Result<()> {
         let redirect_chain_json = serde_json::to_string(&page.redirect_chain)
             .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
         let h1_tags_json = serde_json::to_string(&page.h1_tags)
             .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
         let h2_tags_json = serde_json::to_string(&page.h2_tags)
             .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+        let fingerprint = simhash(&page.content_text) as i64;
 
-        self.conn.execute(
+        self.conn()?.execute(
             "INSERT INTO crawled_pages
              (session_id, url, original_url, redirect_chain, title, meta_description,
               content_text, content_html, content_hash, word_count, page_size,
-              http_status_code, response_time_ms, language, charset, h1_tags,
+              http_status_code, response_time_ms, language, language_confidence, charset, h1_tags,
               h2_tags, meta_keywords, canonical_url, robots_meta, internal_links_count,
-              external_links_count, images_count, crawl_time)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+              external_links_count, images_count, crawl_time, simhash, etag, last_modified)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             params![
                 session_id,
                 &page.url,
@@ -135,6 +281,7 @@ impl WebsiteCrawlerDatabase {
                 page.http_status_code as i64,
                 page.response_time_ms as i64,
                 &page.language,
+                page.language_confidence as f64,
                 &page.charset,
                 h1_tags_json,
                 h2_tags_json,
@@ -144,15 +291,90 @@ impl WebsiteCrawlerDatabase {
                 page.internal_links_count as i64,
                 page.external_links_count as i64,
                 page.images_count as i64,
-                page.crawl_time.to_rfc3339()
+                page.crawl_time.to_rfc3339(),
+                fingerprint,
+                &page.etag,
+                &page.last_modified,
             ],
         )?;
 
+        self.record_recrawl_outcome(&page.url, &page.content_hash, page.crawl_time)?;
+
         Ok(())
     }
 
-    pub fn log_crawl_error(&mut self, session_id: &str, url: &str, error_type: &str, error_msg: &str, status_code: Option<u16>) -> Result<()> {
-        self.conn.execute(
+    /// Updates the adaptive recrawl schedule for `url` after a fetch. If the content hash
+    /// is unchanged since the last crawl, the revisit interval backs off (up to
+    /// `MAX_RECRAWL_INTERVAL_SECS`); if it changed, the interval shrinks back down (to at
+    /// least `MIN_RECRAWL_INTERVAL_SECS`) since the page just proved it's volatile.
+    fn record_recrawl_outcome(&self, url: &str, content_hash: &str, crawl_time: DateTime<Utc>) -> Result<()> {
+        let conn = self.conn()?;
+        let previous: Option<(String, i64)> = conn.query_row(
+            "SELECT content_hash, interval_secs FROM recrawl_schedule WHERE url = ?",
+            params![url],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?;
+
+        let interval_secs = match previous {
+            Some((prev_hash, prev_interval)) if prev_hash == content_hash => {
+                ((prev_interval as f64 * RECRAWL_BACKOFF_FACTOR) as i64).min(MAX_RECRAWL_INTERVAL_SECS)
+            }
+            Some((_, prev_interval)) => {
+                ((prev_interval as f64 * RECRAWL_SHRINK_FACTOR) as i64).max(MIN_RECRAWL_INTERVAL_SECS)
+            }
+            None => INITIAL_RECRAWL_INTERVAL_SECS,
+        };
+
+        let next_due_at = crawl_time + chrono::Duration::seconds(interval_secs);
+        conn.execute(
+            "INSERT INTO recrawl_schedule (url, content_hash, interval_secs, last_crawl_time, next_due_at)
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(url) DO UPDATE SET
+                content_hash = excluded.content_hash,
+                interval_secs = excluded.interval_secs,
+                last_crawl_time = excluded.last_crawl_time,
+                next_due_at = excluded.next_due_at",
+            params![url, content_hash, interval_secs, crawl_time.to_rfc3339(), next_due_at.to_rfc3339()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns up to `limit` URLs whose adaptive recrawl interval has elapsed as of `now`,
+    /// most-stale first.
+    pub fn urls_due_for_recrawl(&self, now: DateTime<Utc>, limit: usize) -> Result<Vec<String>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT url FROM recrawl_schedule WHERE next_due_at <= ? ORDER BY next_due_at ASC LIMIT ?"
+        )?;
+        let urls = stmt.query_map(params![now.to_rfc3339(), limit as i64], |row| row.get(0))?
+            .collect::<Result<Vec<String>>>()?;
+        Ok(urls)
+    }
+
+    /// Returns URLs whose stored SimHash fingerprint is within `max_hamming` bits of
+    /// `fingerprint`, i.e. near-duplicates of the page that produced it.
+    pub fn find_near_duplicates(&self, fingerprint: u64, max_hamming: u32) -> Result<Vec<String>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT url, simhash FROM crawled_pages WHERE simhash IS NOT NULL"
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+
+        let mut matches = Vec::new();
+        for row in rows {
+            let (url, candidate) = row?;
+            if ((candidate as u64) ^ fingerprint).count_ones() <= max_hamming {
+                matches.push(url);
+            }
+        }
+        Ok(matches)
+    }
+
+    pub fn log_crawl_error(&self, session_id: &str, url: &str, error_type: &str, error_msg: &str, status_code: Option<u16>) -> Result<()> {
+        self.conn()?.execute(
             "INSERT INTO crawl_errors (session_id, url, error_type, error_msg, status_code, timestamp)
              VALUES (?, ?, ?, ?, ?, ?)",
             params![
@@ -169,29 +391,97 @@ impl WebsiteCrawlerDatabase {
     }
 
     pub fn get_all_crawled_urls(&self) -> Result<Vec<String>> {
-        let mut stmt = self.conn.prepare("SELECT DISTINCT url FROM crawled_pages")?;
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT DISTINCT url FROM crawled_pages")?;
         let urls = stmt.query_map([], |row| row.get(0))?
             .collect::<Result<Vec<String>>>()?;
         Ok(urls)
     }
 
     pub fn get_all_content_hashes(&self) -> Result<Vec<String>> {
-        let mut stmt = self.conn.prepare("SELECT DISTINCT content_hash FROM crawled_pages")?;
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT DISTINCT content_hash FROM crawled_pages")?;
         let hashes = stmt.query_map([], |row| row.get(0))?
             .collect::<Result<Vec<String>>>()?;
         Ok(hashes)
     }
 
     pub fn get_last_crawl_time(&self, url: &str) -> Result<Option<String>> {
-        let mut stmt = self.conn.prepare(
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
             "SELECT MAX(crawl_time) FROM crawled_pages WHERE url = ?"
         )?;
         let result = stmt.query_row(params![url], |row| row.get(0)).optional()?;
         Ok(result)
     }
 
-    pub fn finish_crawl_session(&mut self, session_id: &str, status: &str) -> Result<()> {
-        self.conn.execute(
+    /// Returns the `(etag, last_modified)` validators recorded for `url`'s most recent
+    /// crawl, if any, so the next fetch can attempt a conditional GET.
+    pub fn get_page_validators(&self, url: &str) -> Result<Option<(String, String)>> {
+        let conn = self.conn()?;
+        conn.query_row(
+            "SELECT etag, last_modified FROM crawled_pages
+             WHERE url = ? AND (etag != '' OR last_modified != '')
+             ORDER BY crawl_time DESC LIMIT 1",
+            params![url],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()
+    }
+
+    /// Returns the most recently stored `crawled_pages` row for `url`, used to reuse a
+    /// page's content when a conditional GET comes back `304 Not Modified`.
+    pub fn get_latest_page(&self, url: &str) -> Result<Option<CrawledPage>> {
+        let conn = self.conn()?;
+        conn.query_row(
+            "SELECT url, original_url, redirect_chain, title, meta_description, content_text,
+                    content_html, content_hash, word_count, page_size, http_status_code,
+                    response_time_ms, language, language_confidence, charset, h1_tags, h2_tags,
+                    meta_keywords, canonical_url, robots_meta, internal_links_count,
+                    external_links_count, images_count, crawl_time, etag, last_modified
+             FROM crawled_pages WHERE url = ? ORDER BY crawl_time DESC LIMIT 1",
+            params![url],
+            |row| {
+                let redirect_chain_json: String = row.get(2)?;
+                let h1_tags_json: String = row.get(15)?;
+                let h2_tags_json: String = row.get(16)?;
+                let crawl_time: String = row.get(23)?;
+                Ok(CrawledPage {
+                    url: row.get(0)?,
+                    original_url: row.get(1)?,
+                    redirect_chain: serde_json::from_str(&redirect_chain_json).unwrap_or_default(),
+                    title: row.get(3)?,
+                    meta_description: row.get(4)?,
+                    content_text: row.get(5)?,
+                    content_html: row.get(6)?,
+                    content_hash: row.get(7)?,
+                    word_count: row.get::<_, i64>(8)? as usize,
+                    page_size: row.get::<_, i64>(9)? as usize,
+                    http_status_code: row.get::<_, i64>(10)? as u16,
+                    response_time_ms: row.get::<_, i64>(11)? as u64,
+                    language: row.get(12)?,
+                    language_confidence: row.get::<_, f64>(13)? as f32,
+                    charset: row.get(14)?,
+                    content_type: String::new(),
+                    h1_tags: serde_json::from_str(&h1_tags_json).unwrap_or_default(),
+                    h2_tags: serde_json::from_str(&h2_tags_json).unwrap_or_default(),
+                    meta_keywords: row.get(17)?,
+                    canonical_url: row.get(18)?,
+                    robots_meta: row.get(19)?,
+                    internal_links_count: row.get::<_, i64>(20)? as usize,
+                    external_links_count: row.get::<_, i64>(21)? as usize,
+                    images_count: row.get::<_, i64>(22)? as usize,
+                    crawl_time: DateTime::parse_from_rfc3339(&crawl_time)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    etag: row.get(24)?,
+                    last_modified: row.get(25)?,
+                })
+            },
+        ).optional()
+    }
+
+    pub fn finish_crawl_session(&self, session_id: &str, status: &str) -> Result<()> {
+        self.conn()?.execute(
             "UPDATE crawl_sessions SET end_time = ?, status = ? WHERE id = ?",
             params![Utc::now().to_rfc3339(), status, session_id],
         )?;
@@ -199,13 +489,14 @@ impl WebsiteCrawlerDatabase {
     }
 
     pub fn get_crawl_summary(&self, session_id: &str) -> Result<HashMap<String, usize>> {
-        let pages_crawled: usize = self.conn.query_row(
+        let conn = self.conn()?;
+        let pages_crawled: usize = conn.query_row(
             "SELECT COUNT(*) FROM crawled_pages WHERE session_id = ?",
             params![session_id],
             |row| row.get(0),
         )?;
 
-        let errors: usize = self.conn.query_row(
+        let errors: usize = conn.query_row(
             "SELECT COUNT(*) FROM crawl_errors WHERE session_id = ?",
             params![session_id],
             |row| row.get(0),
@@ -217,24 +508,153 @@ impl WebsiteCrawlerDatabase {
 
         Ok(summary)
     }
+
+    /// Runs an arbitrary SELECT/EXPLAIN for ad-hoc analysis and returns the rows as JSON.
+    /// In read-only mode, anything other than a `SELECT`/`EXPLAIN` is rejected up front
+    /// in addition to the connection itself being opened `SQLITE_OPEN_READ_ONLY`.
+    pub fn query(&self, sql: &str) -> Result<QueryOutput> {
+        if self.read_only {
+            Self::require_read_only_statement(sql)?;
+        }
+
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(sql)?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+        let rows = stmt.query_map([], |row| {
+            let mut obj = serde_json::Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                obj.insert(column.clone(), sql_value_to_json(row.get_ref(i)?));
+            }
+            Ok(serde_json::Value::Object(obj))
+        })?
+        .collect::<Result<Vec<serde_json::Value>>>()?;
+
+        Ok(QueryOutput { columns, rows })
+    }
+
+    /// Runs a mutating statement and returns the number of affected rows. Refuses to run
+    /// at all when the database was opened with `new_read_only`.
+    pub fn execute(&self, sql: &str) -> Result<usize> {
+        if self.read_only {
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_READONLY),
+                Some("database is open in read-only mode".to_string()),
+            ));
+        }
+        Ok(self.conn()?.execute(sql, [])?)
+    }
+
+    fn require_read_only_statement(sql: &str) -> Result<()> {
+        let normalized = sql.trim_start().to_ascii_uppercase();
+        if normalized.starts_with("SELECT") || normalized.starts_with("EXPLAIN") {
+            Ok(())
+        } else {
+            Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_READONLY),
+                Some("read-only mode only permits SELECT/EXPLAIN statements".to_string()),
+            ))
+        }
+    }
+
+    /// Streams every `crawled_pages` row for `session_id` as JSON Lines, for handing off
+    /// to downstream indexing without holding the whole result set in memory.
+    pub fn export_pages_jsonl(&self, session_id: &str, mut writer: impl std::io::Write) -> Result<()> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT url, original_url, title, meta_description, content_text, content_hash,
+                    word_count, page_size, http_status_code, language, charset, crawl_time, simhash
+             FROM crawled_pages WHERE session_id = ?"
+        )?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+        let mut rows = stmt.query(params![session_id])?;
+        while let Some(row) = rows.next()? {
+            let mut obj = serde_json::Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                obj.insert(column.clone(), sql_value_to_json(row.get_ref(i)?));
+            }
+            let line = serde_json::to_string(&serde_json::Value::Object(obj))
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+            writeln!(writer, "{}", line)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes every stored `crawled_pages` row whose URL's host matches one of
+    /// `weed_domains`, so operators can retroactively clear already-crawled content after
+    /// tightening `CrawlConfig::weed_domains`. Returns the number of rows deleted.
+    pub fn prune_weeded_pages(&self, weed_domains: &[String]) -> Result<usize> {
+        if weed_domains.is_empty() {
+            return Ok(0);
+        }
+
+        let conn = self.conn()?;
+        let ids_to_delete: Vec<i64> = {
+            let mut stmt = conn.prepare("SELECT id, url FROM crawled_pages")?;
+            stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+                .filter_map(|r| r.ok())
+                .filter(|(_, url)| {
+                    extract_host(url)
+                        .map_or(false, |host| weed_domains.iter().any(|d| host_matches(&host, d)))
+                })
+                .map(|(id, _)| id)
+                .collect()
+        };
+
+        for id in &ids_to_delete {
+            conn.execute("DELETE FROM crawled_pages WHERE id = ?", params![id])?;
+        }
+
+        Ok(ids_to_delete.len())
+    }
 }
 
-#[derive(Debug)]
+/// Result of an ad-hoc `query()` call: column names plus each row as a JSON object.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct QueryOutput {
+    pub columns: Vec<String>,
+    pub rows: Vec<serde_json::Value>,
+}
+
+/// Converts a raw SQLite column value into the closest JSON representation.
+fn sql_value_to_json(value: rusqlite::types::ValueRef) -> serde_json::Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => serde_json::Value::from(i),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f).map(serde_json::Value::Number).unwrap_or(serde_json::Value::Null),
+        ValueRef::Text(t) => serde_json::Value::String(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Blob(b) => serde_json::Value::Array(b.iter().map(|byte| serde_json::Value::from(*byte)).collect()),
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct BacklinkDatabase {
-    conn: Connection,
+    pool: Pool<SqliteConnectionManager>,
 }
 
 impl BacklinkDatabase {
     pub fn new(db_path: &str) -> Result<Self> {
-        let conn = Connection::open(db_path)?;
-        let mut db = Self { conn };
+        let pool = new_pool(db_path)?;
+        let db = Self { pool };
         db.init_database()?;
         Ok(db)
     }
 
-    fn init_database(&mut self) -> Result<()> {
+    fn conn(&self) -> Result<PooledConnection<SqliteConnectionManager>> {
+        self.pool.get().map_err(|e| rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            Some(e.to_string()),
+        ))
+    }
+
+    fn init_database(&self) -> Result<()> {
+        let conn = self.conn()?;
         // Backlinks table
-        self.conn.execute(
+        conn.execute(
             "CREATE TABLE IF NOT EXISTS backlinks (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 source_url TEXT NOT NULL,
@@ -251,7 +671,7 @@ impl BacklinkDatabase {
         )?;
 
         // Domain scores table
-        self.conn.execute(
+        conn.execute(
             "CREATE TABLE IF NOT EXISTS domain_scores (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 domain TEXT UNIQUE NOT NULL,
@@ -264,7 +684,7 @@ impl BacklinkDatabase {
         )?;
 
         // PageRank scores table
-        self.conn.execute(
+        conn.execute(
             "CREATE TABLE IF NOT EXISTS pagerank_scores (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 url TEXT UNIQUE NOT NULL,
@@ -277,9 +697,10 @@ impl BacklinkDatabase {
         Ok(())
     }
 
-    pub fn store_backlinks(&mut self, backlinks: &[BacklinkData]) -> Result<()> {
+    pub fn store_backlinks(&self, backlinks: &[BacklinkData]) -> Result<()> {
+        let conn = self.conn()?;
         for backlink in backlinks {
-            self.conn.execute(
+            conn.execute(
                 "INSERT OR REPLACE INTO backlinks
                  (source_url, target_url, anchor_text, context, page_title,
                   domain_authority, is_nofollow, crawl_date)
@@ -300,7 +721,8 @@ impl BacklinkDatabase {
     }
 
     pub fn get_backlinks_for_url(&self, target_url: &str) -> Result<Vec<BacklinkData>> {
-        let mut stmt = self.conn.prepare(
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
             "SELECT source_url, target_url, anchor_text, context, page_title,
                     domain_authority, is_nofollow, crawl_date
              FROM backlinks WHERE target_url = ? ORDER BY crawl_date DESC"
@@ -325,9 +747,10 @@ impl BacklinkDatabase {
         Ok(backlinks)
     }
 
-    pub fn store_domain_scores(&mut self, domain_scores: &HashMap<String, f64>) -> Result<()> {
+    pub fn store_domain_scores(&self, domain_scores: &HashMap<String, f64>) -> Result<()> {
+        let conn = self.conn()?;
         for (domain, score) in domain_scores {
-            self.conn.execute(
+            conn.execute(
                 "INSERT OR REPLACE INTO domain_scores (domain, authority_score, last_updated)
                  VALUES (?, ?, ?)",
                 params![domain, score, Utc::now().to_rfc3339()],
@@ -336,9 +759,10 @@ impl BacklinkDatabase {
         Ok(())
     }
 
-    pub fn store_pagerank_scores(&mut self, pagerank_scores: &HashMap<String, f64>) -> Result<()> {
+    pub fn store_pagerank_scores(&self, pagerank_scores: &HashMap<String, f64>) -> Result<()> {
+        let conn = self.conn()?;
         for (url, score) in pagerank_scores {
-            self.conn.execute(
+            conn.execute(
                 "INSERT OR REPLACE INTO pagerank_scores (url, pagerank_score, last_calculated)
                  VALUES (?, ?, ?)",
                 params![url, score, Utc::now().to_rfc3339()],
@@ -346,4 +770,181 @@ impl BacklinkDatabase {
         }
         Ok(())
     }
+
+    /// Runs power-iteration PageRank over the `backlinks` edge list and persists the
+    /// result via `store_pagerank_scores`. Dangling nodes (no outgoing edges) spread
+    /// their mass uniformly across every node so the total rank stays at 1.0.
+    pub fn compute_pagerank(&self, damping: f64, max_iters: usize, tolerance: f64) -> Result<HashMap<String, f64>> {
+        let edges = {
+            let conn = self.conn()?;
+            let mut stmt = conn.prepare(
+                "SELECT source_url, target_url FROM backlinks WHERE is_nofollow = 0"
+            )?;
+            stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<Result<Vec<(String, String)>>>()?
+        };
+
+        // Build an index over every distinct URL seen as either a source or a target.
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for (source, target) in &edges {
+            let next_id = index.len();
+            index.entry(source.clone()).or_insert(next_id);
+            let next_id = index.len();
+            index.entry(target.clone()).or_insert(next_id);
+        }
+        let n = index.len();
+        if n == 0 {
+            return Ok(HashMap::new());
+        }
+
+        let mut out_degree = vec![0usize; n];
+        let mut out_links: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (source, target) in &edges {
+            let from = index[source];
+            let to = index[target];
+            out_degree[from] += 1;
+            out_links[from].push(to);
+        }
+
+        let mut rank = vec![1.0 / n as f64; n];
+        for _ in 0..max_iters {
+            let dangling_mass: f64 = (0..n)
+                .filter(|&i| out_degree[i] == 0)
+                .map(|i| rank[i])
+                .sum();
+
+            let mut next_rank = vec![(1.0 - damping) / n as f64 + damping * dangling_mass / n as f64; n];
+            for from in 0..n {
+                if out_degree[from] == 0 {
+                    continue;
+                }
+                let share = damping * rank[from] / out_degree[from] as f64;
+                for &to in &out_links[from] {
+                    next_rank[to] += share;
+                }
+            }
+
+            let delta: f64 = rank.iter().zip(next_rank.iter())
+                .map(|(old, new)| (old - new).abs())
+                .sum();
+            rank = next_rank;
+            if delta < tolerance {
+                break;
+            }
+        }
+
+        let scores: HashMap<String, f64> = index.into_iter()
+            .map(|(url, id)| (url, rank[id]))
+            .collect();
+
+        self.store_pagerank_scores(&scores)?;
+        Ok(scores)
+    }
+
+    /// Aggregates total inbound backlinks and distinct referring domains per target
+    /// domain, folds in each page's PageRank, and writes a 0-100 authority score plus
+    /// the raw counts into `domain_scores` in a single transaction.
+    pub fn compute_domain_scores(&self) -> Result<HashMap<String, f64>> {
+        let pageranks = self.compute_pagerank(0.85, 100, 1e-6)?;
+
+        let edges = {
+            let conn = self.conn()?;
+            let mut stmt = conn.prepare("SELECT source_url, target_url FROM backlinks")?;
+            stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<Result<Vec<(String, String)>>>()?
+        };
+
+        struct DomainAggregate {
+            total_backlinks: usize,
+            referring_domains: std::collections::HashSet<String>,
+            target_pages: std::collections::HashSet<String>,
+        }
+
+        let mut aggregates: HashMap<String, DomainAggregate> = HashMap::new();
+        for (source_url, target_url) in &edges {
+            let Some(target_domain) = extract_host(target_url) else { continue };
+            let source_domain = extract_host(source_url).unwrap_or_else(|| source_url.clone());
+            let entry = aggregates.entry(target_domain).or_insert_with(|| DomainAggregate {
+                total_backlinks: 0,
+                referring_domains: std::collections::HashSet::new(),
+                target_pages: std::collections::HashSet::new(),
+            });
+            entry.total_backlinks += 1;
+            entry.referring_domains.insert(source_domain);
+            entry.target_pages.insert(target_url.clone());
+        }
+
+        let mut scores = HashMap::new();
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        for (domain, agg) in &aggregates {
+            // Each distinct target page's PageRank counts once, not once per inbound edge.
+            let pagerank_sum: f64 = agg.target_pages.iter()
+                .map(|url| pageranks.get(url).copied().unwrap_or(0.0))
+                .sum();
+            // Log-scaled blend of referring-domain breadth and accumulated PageRank, capped at 100.
+            let authority_score = (10.0 * (agg.referring_domains.len() as f64 + 1.0).ln()
+                + 20.0 * (pagerank_sum * 100.0 + 1.0).ln())
+                .min(100.0);
+
+            tx.execute(
+                "INSERT INTO domain_scores (domain, authority_score, total_backlinks, unique_referring_domains, last_updated)
+                 VALUES (?, ?, ?, ?, ?)
+                 ON CONFLICT(domain) DO UPDATE SET
+                    authority_score = excluded.authority_score,
+                    total_backlinks = excluded.total_backlinks,
+                    unique_referring_domains = excluded.unique_referring_domains,
+                    last_updated = excluded.last_updated",
+                params![
+                    domain,
+                    authority_score,
+                    agg.total_backlinks as i64,
+                    agg.referring_domains.len() as i64,
+                    Utc::now().to_rfc3339()
+                ],
+            )?;
+
+            scores.insert(domain.clone(), authority_score);
+        }
+        tx.commit()?;
+
+        Ok(scores)
+    }
+
+    /// Deletes every stored `backlinks` row whose `source_url` or `target_url` host matches
+    /// one of `weed_domains`, mirroring `WebsiteCrawlerDatabase::prune_weeded_pages` so
+    /// tightening `CrawlerConfig::weed_domains` can retroactively clear already-discovered
+    /// backlinks too. Returns the number of rows deleted.
+    pub fn prune_weeded_backlinks(&self, weed_domains: &[String]) -> Result<usize> {
+        if weed_domains.is_empty() {
+            return Ok(0);
+        }
+
+        let conn = self.conn()?;
+        let ids_to_delete: Vec<i64> = {
+            let mut stmt = conn.prepare("SELECT id, source_url, target_url FROM backlinks")?;
+            stmt.query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })?
+                .filter_map(|r| r.ok())
+                .filter(|(_, source_url, target_url)| {
+                    [source_url, target_url].into_iter().any(|url| {
+                        extract_host(url).map_or(false, |host| weed_domains.iter().any(|d| host_matches(&host, d)))
+                    })
+                })
+                .map(|(id, _, _)| id)
+                .collect()
+        };
+
+        for id in &ids_to_delete {
+            conn.execute("DELETE FROM backlinks WHERE id = ?", params![id])?;
+        }
+
+        Ok(ids_to_delete.len())
+    }
+}
+
+/// Extracts the host (e.g. `example.com`) from a URL string, used to group backlinks by domain.
+fn extract_host(url: &str) -> Option<String> {
+    url::Url::parse(url).ok()?.host_str().map(|h| h.to_string())
 }
@@ -1,31 +1,40 @@
-use crate::models::{BacklinkData, CrawlResult, SeedUrl, DashboardStats};
+use crate::models::{BacklinkData, CrawlResult, SeedUrl, DashboardStats, ProcessingSession, SanitizePolicy, ContentChange};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, Row};
-use std::path::Path;
+use r2d2::{CustomizeConnection, Pool, PooledConnection};
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use log::{info, warn, error};
+use url::Url;
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Schema, STORED, STRING, TEXT};
+use tantivy::{doc, Index, IndexWriter, Term};
+use scraper::{Html, Selector};
 
-pub struct Database {
-    conn: Arc<Mutex<Connection>>,
-}
-
-impl Database {
-    pub fn new<P: AsRef<Path>>(db_path: P) -> Result<Self> {
-        let conn = Connection::open(db_path)?;
-        let db = Self {
-            conn: Arc::new(Mutex::new(conn)),
-        };
-        db.init_tables()?;
-        Ok(db)
-    }
+const SEARCH_INDEX_COMMIT_INTERVAL_SECS: u64 = 30 * 60;
+const DEFAULT_POOL_MAX_SIZE: u32 = 8;
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
 
-    fn init_tables(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+/// A single schema migration, applied once when `PRAGMA user_version` is below its index
+/// in [`MIGRATIONS`]. Mirrors the ordered-migration-list workflow of diesel-migrations,
+/// but keeps the SQL inline rather than in separate files since the schema is small.
+struct Migration {
+    name: &'static str,
+    sql: &'static str,
+}
 
-        // Create backlinks table
-        conn.execute(
-            r#"
+/// Ordered schema migrations. Never edit or reorder an existing entry once it has shipped;
+/// append new ones instead so `PRAGMA user_version` stays meaningful across upgrades.
+static MIGRATIONS: &[Migration] = &[
+    Migration {
+        name: "001_initial_schema",
+        sql: r#"
             CREATE TABLE IF NOT EXISTS backlinks (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 source_url TEXT NOT NULL,
@@ -37,14 +46,8 @@ impl Database {
                 is_nofollow BOOLEAN DEFAULT 0,
                 discovered_at TEXT NOT NULL,
                 UNIQUE(source_url, target_url)
-            )
-            "#,
-            [],
-        )?;
+            );
 
-        // Create crawl_results table
-        conn.execute(
-            r#"
             CREATE TABLE IF NOT EXISTS crawl_results (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 url TEXT NOT NULL UNIQUE,
@@ -74,14 +77,8 @@ impl Database {
                 crawl_success BOOLEAN DEFAULT 0,
                 error_message TEXT,
                 crawled_at TEXT NOT NULL
-            )
-            "#,
-            [],
-        )?;
+            );
 
-        // Create seed_urls table
-        conn.execute(
-            r#"
             CREATE TABLE IF NOT EXISTS seed_urls (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 url TEXT NOT NULL UNIQUE,
@@ -89,14 +86,8 @@ impl Database {
                 priority INTEGER DEFAULT 1,
                 last_crawled TEXT,
                 crawl_count INTEGER DEFAULT 0
-            )
-            "#,
-            [],
-        )?;
+            );
 
-        // Create stats table for dashboard
-        conn.execute(
-            r#"
             CREATE TABLE IF NOT EXISTS stats (
                 id INTEGER PRIMARY KEY,
                 total_urls_crawled INTEGER DEFAULT 0,
@@ -110,169 +101,1163 @@ impl Database {
                 last_updated TEXT NOT NULL,
                 current_mode TEXT DEFAULT 'idle',
                 next_mode_switch TEXT
-            )
-            "#,
-            [],
-        )?;
+            );
 
-        // Insert initial stats row if it doesn't exist
-        conn.execute(
-            "INSERT OR IGNORE INTO stats (id, last_updated) VALUES (1, ?)",
-            params![Utc::now().to_rfc3339()],
-        )?;
+            INSERT OR IGNORE INTO stats (id, last_updated) VALUES (1, '1970-01-01T00:00:00+00:00');
 
-        // Create indexes for better performance
-        let indexes = [
-            "CREATE INDEX IF NOT EXISTS idx_backlinks_source ON backlinks(source_url)",
-            "CREATE INDEX IF NOT EXISTS idx_backlinks_target ON backlinks(target_url)",
-            "CREATE INDEX IF NOT EXISTS idx_crawl_results_url ON crawl_results(url)",
-            "CREATE INDEX IF NOT EXISTS idx_crawl_results_crawled_at ON crawl_results(crawled_at)",
-            "CREATE INDEX IF NOT EXISTS idx_seed_urls_priority ON seed_urls(priority DESC)",
-        ];
-
-        for index_sql in &indexes {
-            conn.execute(index_sql, [])?;
-        }
+            CREATE INDEX IF NOT EXISTS idx_backlinks_source ON backlinks(source_url);
+            CREATE INDEX IF NOT EXISTS idx_backlinks_target ON backlinks(target_url);
+            CREATE INDEX IF NOT EXISTS idx_crawl_results_url ON crawl_results(url);
+            CREATE INDEX IF NOT EXISTS idx_crawl_results_crawled_at ON crawl_results(crawled_at);
+            CREATE INDEX IF NOT EXISTS idx_seed_urls_priority ON seed_urls(priority DESC);
+        "#,
+    },
+    Migration {
+        name: "002_domain_policy",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS allowed_domains (
+                domain TEXT PRIMARY KEY,
+                added_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS weeded_domains (
+                domain TEXT PRIMARY KEY,
+                added_at TEXT NOT NULL
+            );
+        "#,
+    },
+    Migration {
+        name: "003_simhash",
+        sql: r#"
+            ALTER TABLE crawl_results ADD COLUMN simhash INTEGER;
+            ALTER TABLE crawl_results ADD COLUMN simhash_band INTEGER;
+            CREATE INDEX IF NOT EXISTS idx_crawl_results_simhash_band ON crawl_results(simhash_band);
+        "#,
+    },
+    Migration {
+        name: "004_spam_tokens",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS spam_tokens (
+                hash_h1 INTEGER NOT NULL,
+                hash_h2 INTEGER NOT NULL,
+                spam_count INTEGER NOT NULL DEFAULT 0,
+                ham_count INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (hash_h1, hash_h2)
+            );
+        "#,
+    },
+    Migration {
+        name: "005_processing_sessions",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS processing_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_type TEXT NOT NULL,
+                start_time TEXT NOT NULL,
+                end_time TEXT,
+                items_processed INTEGER NOT NULL DEFAULT 0,
+                errors INTEGER NOT NULL DEFAULT 0,
+                status TEXT NOT NULL DEFAULT 'running'
+            );
 
-        info!("Database tables initialized successfully");
+            CREATE INDEX IF NOT EXISTS idx_processing_sessions_type ON processing_sessions(session_type);
+            CREATE INDEX IF NOT EXISTS idx_processing_sessions_status ON processing_sessions(status);
+            CREATE INDEX IF NOT EXISTS idx_processing_sessions_start_time ON processing_sessions(start_time);
+        "#,
+    },
+    Migration {
+        name: "006_pagerank_scores",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS pagerank_scores (
+                url TEXT PRIMARY KEY,
+                pagerank_score REAL NOT NULL DEFAULT 0.0,
+                last_calculated TEXT NOT NULL
+            );
+        "#,
+    },
+    Migration {
+        name: "007_content_change_tracking",
+        sql: r#"
+            ALTER TABLE crawl_results ADD COLUMN last_seen TEXT;
+            UPDATE crawl_results SET last_seen = crawled_at WHERE last_seen IS NULL;
+
+            CREATE TABLE IF NOT EXISTS content_changes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                url TEXT NOT NULL,
+                diff TEXT NOT NULL,
+                changed_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_content_changes_url ON content_changes(url);
+            CREATE INDEX IF NOT EXISTS idx_content_changes_changed_at ON content_changes(changed_at DESC);
+        "#,
+    },
+    Migration {
+        name: "008_simhash_multi_probe_bands",
+        sql: r#"
+            CREATE INDEX IF NOT EXISTS idx_crawl_results_simhash_band1 ON crawl_results((simhash & 65535));
+            CREATE INDEX IF NOT EXISTS idx_crawl_results_simhash_band2 ON crawl_results(((simhash >> 16) & 65535));
+            CREATE INDEX IF NOT EXISTS idx_crawl_results_simhash_band3 ON crawl_results(((simhash >> 32) & 65535));
+        "#,
+    },
+];
+
+#[derive(Debug)]
+struct ConnectionCustomizer;
+
+impl CustomizeConnection<Connection, rusqlite::Error> for ConnectionCustomizer {
+    fn on_acquire(&self, conn: &mut Connection) -> std::result::Result<(), rusqlite::Error> {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
         Ok(())
     }
+}
 
-    pub async fn save_backlinks(&self, backlinks: &[BacklinkData]) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+pub struct Database {
+    pool: Pool<SqliteConnectionManager>,
+    search_index: Index,
+    search_writer: Arc<Mutex<IndexWriter>>,
+    search_fields: SearchFields,
+    sanitize_policy: SanitizePolicy,
+}
+
+#[derive(Clone, Copy)]
+struct SearchFields {
+    url: tantivy::schema::Field,
+    title: tantivy::schema::Field,
+    meta_description: tantivy::schema::Field,
+    content_text: tantivy::schema::Field,
+}
+
+fn build_search_schema() -> (Schema, SearchFields) {
+    let mut builder = Schema::builder();
+    let url = builder.add_text_field("url", STRING | STORED);
+    let title = builder.add_text_field("title", TEXT | STORED);
+    let meta_description = builder.add_text_field("meta_description", TEXT);
+    let content_text = builder.add_text_field("content_text", TEXT);
+    let schema = builder.build();
+    (schema, SearchFields { url, title, meta_description, content_text })
+}
+
+fn search_index_dir(db_path: &Path) -> PathBuf {
+    let mut dir = db_path.to_path_buf();
+    let file_name = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    dir.set_file_name(format!("{}.search_index", file_name));
+    dir
+}
+
+/// Computes a 64-bit SimHash fingerprint over the word tokens of `text`, used to detect
+/// near-duplicate pages that differ only by boilerplate (timestamps, nav tweaks, ads).
+fn simhash(text: &str) -> i64 {
+    let mut bit_sums = [0i64; 64];
+
+    for token in text.split_whitespace().map(|t| t.to_ascii_lowercase()) {
+        if token.is_empty() {
+            continue;
+        }
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        token.hash(&mut hasher);
+        let token_hash = hasher.finish();
+
+        for (bit, sum) in bit_sums.iter_mut().enumerate() {
+            if token_hash & (1u64 << bit) != 0 {
+                *sum += 1;
+            } else {
+                *sum -= 1;
+            }
+        }
+    }
+
+    let mut fingerprint: u64 = 0;
+    for (bit, sum) in bit_sums.iter().enumerate() {
+        if *sum > 0 {
+            fingerprint |= 1u64 << bit;
+        }
+    }
+    fingerprint as i64
+}
+
+/// The high 16 bits of a fingerprint. This is one of four non-overlapping 16-bit probe bands
+/// (see [`Database::find_near_duplicates`]) used to bucket near-duplicate candidates so a
+/// Hamming scan only has to touch a small slice of rows instead of the whole table.
+fn simhash_band(fingerprint: i64) -> i64 {
+    ((fingerprint as u64) >> 48) as i64
+}
+
+/// True when `host` is exactly `domain` or a subdomain of it.
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+fn open_search_index(index_dir: &Path) -> Result<(Index, Schema)> {
+    std::fs::create_dir_all(index_dir)?;
+    let (schema, _) = build_search_schema();
+    let index = if index_dir.read_dir()?.next().is_some() {
+        Index::open_in_dir(index_dir)?
+    } else {
+        Index::create_in_dir(index_dir, schema.clone())?
+    };
+    Ok((index, schema))
+}
+
+impl Database {
+    pub fn new<P: AsRef<Path>>(db_path: P) -> Result<Self> {
+        Self::with_pool_size(db_path, DEFAULT_POOL_MAX_SIZE)
+    }
+
+    pub fn with_pool_size<P: AsRef<Path>>(db_path: P, max_pool_size: u32) -> Result<Self> {
+        let manager = SqliteConnectionManager::file(db_path.as_ref());
+        let pool = Pool::builder()
+            .max_size(max_pool_size)
+            .connection_customizer(Box::new(ConnectionCustomizer))
+            .build(manager)?;
+
+        let (_, search_fields) = build_search_schema();
+        let index_dir = search_index_dir(db_path.as_ref());
+        let (search_index, _) = open_search_index(&index_dir)?;
+        let search_writer = search_index.writer(50_000_000)?;
+
+        let db = Self {
+            pool,
+            search_index,
+            search_writer: Arc::new(Mutex::new(search_writer)),
+            search_fields,
+            sanitize_policy: SanitizePolicy::default(),
+        };
+        db.init_tables()?;
+
+        if db.search_index.reader()?.searcher().num_docs() == 0 {
+            if let Err(e) = db.reindex_all() {
+                warn!("Failed to build initial search index: {}", e);
+            }
+        }
+
+        db.spawn_search_commit_task();
+
+        Ok(db)
+    }
+
+    /// Overrides the HTML allowlist `save_crawl_result` sanitizes through. Defaults to
+    /// [`SanitizePolicy::default`].
+    pub fn with_sanitize_policy(mut self, policy: SanitizePolicy) -> Self {
+        self.sanitize_policy = policy;
+        self
+    }
+
+    /// Cleans `html` through `self.sanitize_policy`'s tag/attribute allowlist and derives
+    /// whitespace-normalized body text from the cleaned DOM.
+    fn sanitize_html(&self, html: &str) -> (String, String, usize) {
+        let mut builder = ammonia::Builder::default();
+        let tags: std::collections::HashSet<&str> = self
+            .sanitize_policy
+            .allowed_tags
+            .iter()
+            .map(String::as_str)
+            .collect();
+        builder.tags(tags);
+
+        let mut attributes: std::collections::HashMap<&str, std::collections::HashSet<&str>> = std::collections::HashMap::new();
+        for (tag, attrs) in &self.sanitize_policy.allowed_attributes {
+            if self.sanitize_policy.strip_links && tag == "a" {
+                continue;
+            }
+            attributes.insert(tag.as_str(), attrs.iter().map(String::as_str).collect());
+        }
+        builder.tag_attributes(attributes);
+        if self.sanitize_policy.strip_links {
+            builder.link_rel(None);
+        }
+
+        let cleaned_html = builder.clean(html).to_string();
+
+        let document = Html::parse_fragment(&cleaned_html);
+        let body_selector = Selector::parse("body").unwrap();
+        let text = document
+            .select(&body_selector)
+            .next()
+            .map(|el| el.text().collect::<Vec<_>>().join(" "))
+            .unwrap_or_else(|| document.root_element().text().collect::<Vec<_>>().join(" "));
+        let normalized_text: String = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let word_count = normalized_text.split_whitespace().count();
+
+        (cleaned_html, normalized_text, word_count)
+    }
+
+    fn conn(&self) -> Result<PooledConnection<SqliteConnectionManager>> {
+        Ok(self.pool.get()?)
+    }
+
+    /// Runs a blocking rusqlite closure against a pooled connection on the blocking
+    /// thread pool, so callers on the async runtime never block waiting for SQLite.
+    async fn with_conn<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || -> Result<T> {
+            let conn = pool.get()?;
+            Ok(f(&conn)?)
+        })
+        .await?
+    }
+
+    /// Spawns the periodic search-index commit loop. A no-op (with a log line) outside a
+    /// Tokio runtime, so constructing a `Database` from a sync context (CLI init, tests)
+    /// never panics on "there is no reactor running" — it just skips the background commit.
+    fn spawn_search_commit_task(&self) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            warn!("No Tokio runtime running; skipping background search index commit task");
+            return;
+        };
+        let writer = self.search_writer.clone();
+        handle.spawn(async move {
+            let mut interval = tokio::time::interval(
+                std::time::Duration::from_secs(SEARCH_INDEX_COMMIT_INTERVAL_SECS),
+            );
+            loop {
+                interval.tick().await;
+                let writer = writer.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    writer.lock().unwrap().commit()
+                }).await;
+                match result {
+                    Ok(Ok(_)) => info!("Committed search index on background interval"),
+                    Ok(Err(e)) => error!("Failed to commit search index: {}", e),
+                    Err(e) => error!("Search index commit task panicked: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Rebuilds the search index from every row in `crawl_results`, for use on startup
+    /// when the index is missing or empty.
+    pub fn reindex_all(&self) -> Result<()> {
+        let conn = self.conn()?;
         let mut stmt = conn.prepare(
-            r#"
-            INSERT OR REPLACE INTO backlinks
-            (source_url, target_url, anchor_text, context, page_title, domain_authority, is_nofollow, discovered_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-            "#,
+            "SELECT url, title, meta_description, content_text FROM crawl_results",
         )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?;
+
+        let mut writer = self.search_writer.lock().unwrap();
+        writer.delete_all_documents()?;
+        for row in rows {
+            let (url, title, meta_description, content_text) = row?;
+            writer.add_document(doc!(
+                self.search_fields.url => url,
+                self.search_fields.title => title.unwrap_or_default(),
+                self.search_fields.meta_description => meta_description.unwrap_or_default(),
+                self.search_fields.content_text => content_text.unwrap_or_default(),
+            ))?;
+        }
+        writer.commit()?;
+        info!("Rebuilt search index from crawl_results");
+        Ok(())
+    }
+
+    /// Runs a Tantivy query over indexed pages and rehydrates matching rows from SQLite.
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<CrawlResult>> {
+        let reader = self.search_index.reader()?;
+        let searcher = reader.searcher();
+        let query_parser = QueryParser::for_index(
+            &self.search_index,
+            vec![
+                self.search_fields.title,
+                self.search_fields.meta_description,
+                self.search_fields.content_text,
+            ],
+        );
+        let parsed_query = query_parser.parse_query(query)?;
+        let top_docs = searcher.search(&parsed_query, &TopDocs::with_limit(limit))?;
 
-        for backlink in backlinks {
-            stmt.execute(params![
-                backlink.source_url,
-                backlink.target_url,
-                backlink.anchor_text,
-                backlink.context,
-                backlink.page_title,
-                backlink.domain_authority,
-                backlink.is_nofollow,
-                backlink.discovered_at.to_rfc3339(),
-            ])?;
+        let mut urls = Vec::with_capacity(top_docs.len());
+        for (_score, doc_address) in top_docs {
+            let retrieved = searcher.doc(doc_address)?;
+            if let Some(url) = retrieved.get_first(self.search_fields.url).and_then(|v| v.as_text()) {
+                urls.push(url.to_string());
+            }
         }
 
-        info!("Saved {} backlinks to database", backlinks.len());
+        let mut results = Vec::with_capacity(urls.len());
+        for url in urls {
+            if let Some(result) = self.get_crawl_result_by_url(&url).await? {
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+
+    async fn get_crawl_result_by_url(&self, url: &str) -> Result<Option<CrawlResult>> {
+        let url = url.to_string();
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT url, original_url, redirect_chain, title, meta_description, content_text, content_html,
+                       content_hash, word_count, page_size, http_status_code, response_time_ms, language,
+                       charset, h1_tags, h2_tags, meta_keywords, canonical_url, robots_meta,
+                       internal_links_count, external_links_count, images_count, content_type,
+                       file_extension, crawl_success, error_message, crawled_at
+                FROM crawl_results WHERE url = ?1
+                "#,
+            )?;
+            stmt.query_row(params![url], Self::row_to_crawl_result).optional()
+        })
+        .await
+    }
+
+    fn row_to_crawl_result(row: &Row) -> rusqlite::Result<CrawlResult> {
+        Ok(CrawlResult {
+            url: row.get(0)?,
+            original_url: row.get(1)?,
+            redirect_chain: row.get::<_, Option<String>>(2)?
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            title: row.get(3)?,
+            meta_description: row.get(4)?,
+            content_text: row.get(5)?,
+            content_html: row.get(6)?,
+            content_hash: row.get(7)?,
+            word_count: row.get(8)?,
+            page_size: row.get(9)?,
+            http_status_code: row.get(10)?,
+            response_time_ms: row.get(11)?,
+            language: row.get(12)?,
+            charset: row.get(13)?,
+            h1_tags: row.get::<_, Option<String>>(14)?
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            h2_tags: row.get::<_, Option<String>>(15)?
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            meta_keywords: row.get::<_, Option<String>>(16)?
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            canonical_url: row.get(17)?,
+            robots_meta: row.get(18)?,
+            internal_links_count: row.get(19)?,
+            external_links_count: row.get(20)?,
+            images_count: row.get(21)?,
+            content_type: row.get(22)?,
+            file_extension: row.get(23)?,
+            crawl_success: row.get(24)?,
+            error_message: row.get(25)?,
+            crawled_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(26)?)
+                .map_err(|_| rusqlite::Error::InvalidColumnType(26, "crawled_at".to_string(), rusqlite::types::Type::Text))?
+                .with_timezone(&Utc),
+        })
+    }
+
+    fn index_crawl_result(&self, result: &CrawlResult) -> Result<()> {
+        let mut writer = self.search_writer.lock().unwrap();
+        writer.delete_term(Term::from_field_text(self.search_fields.url, &result.url));
+        writer.add_document(doc!(
+            self.search_fields.url => result.url.clone(),
+            self.search_fields.title => result.title.clone().unwrap_or_default(),
+            self.search_fields.meta_description => result.meta_description.clone().unwrap_or_default(),
+            self.search_fields.content_text => result.content_text.clone().unwrap_or_default(),
+        ))?;
         Ok(())
     }
 
-    pub async fn save_crawl_result(&self, result: &CrawlResult) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+    /// Applies every migration whose version is greater than the database's current
+    /// `PRAGMA user_version`, each inside its own transaction, bumping the version as it
+    /// commits. Fails loudly rather than silently skipping schema changes if the on-disk
+    /// version is newer than this binary's migration list knows about.
+    fn init_tables(&self) -> Result<()> {
+        let mut conn = self.conn()?;
+        let current_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let latest_version = MIGRATIONS.len() as i64;
 
-        conn.execute(
-            r#"
-            INSERT OR REPLACE INTO crawl_results
-            (url, original_url, redirect_chain, title, meta_description, content_text, content_html,
-             content_hash, word_count, page_size, http_status_code, response_time_ms, language,
-             charset, h1_tags, h2_tags, meta_keywords, canonical_url, robots_meta,
-             internal_links_count, external_links_count, images_count, content_type,
-             file_extension, crawl_success, error_message, crawled_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27)
-            "#,
-            params![
-                result.url,
-                result.original_url,
-                result.redirect_chain.as_ref().map(|v| serde_json::to_string(v).unwrap_or_default()),
-                result.title,
-                result.meta_description,
-                result.content_text,
-                result.content_html,
-                result.content_hash,
-                result.word_count,
-                result.page_size,
-                result.http_status_code,
-                result.response_time_ms,
-                result.language,
-                result.charset,
-                result.h1_tags.as_ref().map(|v| serde_json::to_string(v).unwrap_or_default()),
-                result.h2_tags.as_ref().map(|v| serde_json::to_string(v).unwrap_or_default()),
-                result.meta_keywords.as_ref().map(|v| serde_json::to_string(v).unwrap_or_default()),
-                result.canonical_url,
-                result.robots_meta,
-                result.internal_links_count,
-                result.external_links_count,
-                result.images_count,
-                result.content_type,
-                result.file_extension,
-                result.crawl_success,
-                result.error_message,
-                result.crawled_at.to_rfc3339(),
-            ],
-        )?;
+        if current_version > latest_version {
+            anyhow::bail!(
+                "Database schema version {} is newer than this binary supports (knows up to {}); refusing to touch it",
+                current_version,
+                latest_version
+            );
+        }
+
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            let version = (i + 1) as i64;
+            if version <= current_version {
+                continue;
+            }
+
+            let tx = conn.transaction()?;
+            if migration.name == "003_simhash" {
+                // Databases seeded by an earlier, pre-migration version of this schema may
+                // already carry these columns (with user_version still at 0), so the plain
+                // ALTER TABLE below would fail with "duplicate column name". Skip columns that
+                // already exist and only add what's missing.
+                let simhash_count: i64 = tx.query_row(
+                    "SELECT COUNT(*) FROM pragma_table_info('crawl_results') WHERE name = 'simhash'",
+                    [],
+                    |row| row.get(0),
+                )?;
+                if simhash_count == 0 {
+                    tx.execute_batch(migration.sql)?;
+                } else {
+                    tx.execute_batch(
+                        "CREATE INDEX IF NOT EXISTS idx_crawl_results_simhash_band ON crawl_results(simhash_band);",
+                    )?;
+                }
+            } else {
+                tx.execute_batch(migration.sql)?;
+            }
+            tx.pragma_update(None, "user_version", version)?;
+            tx.commit()?;
+            info!("Applied migration {} ({})", version, migration.name);
+        }
 
         Ok(())
     }
 
-    pub async fn get_seed_urls(&self, limit: i32) -> Result<Vec<SeedUrl>> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT url, added_at, priority, last_crawled, crawl_count
-             FROM seed_urls
-             ORDER BY priority DESC, last_crawled ASC NULLS FIRST
-             LIMIT ?",
-        )?;
+    /// The schema version currently recorded in `PRAGMA user_version`.
+    pub fn current_schema_version(&self) -> Result<i64> {
+        let conn = self.conn()?;
+        Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+    }
+
+    pub async fn add_allowed_domain(&self, domain: &str) -> Result<()> {
+        let domain = domain.to_ascii_lowercase();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO allowed_domains (domain, added_at) VALUES (?1, ?2)",
+                params![domain, Utc::now().to_rfc3339()],
+            )?;
+            Ok(())
+        })
+        .await
+    }
 
-        let seed_iter = stmt.query_map(params![limit], |row| {
-            Ok(SeedUrl {
-                url: row.get(0)?,
-                added_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
-                    .map_err(|_| rusqlite::Error::InvalidColumnType(1, "added_at".to_string(), rusqlite::types::Type::Text))?
-                    .with_timezone(&Utc),
-                priority: row.get(2)?,
-                last_crawled: row.get::<_, Option<String>>(3)?
-                    .map(|s| DateTime::parse_from_rfc3339(&s).ok())
-                    .flatten()
-                    .map(|dt| dt.with_timezone(&Utc)),
-                crawl_count: row.get(4)?,
+    pub async fn add_weeded_domain(&self, domain: &str) -> Result<()> {
+        let domain = domain.to_ascii_lowercase();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO weeded_domains (domain, added_at) VALUES (?1, ?2)",
+                params![domain, Utc::now().to_rfc3339()],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Checks a URL's host against the allow/weed lists. A host matches an entry when it
+    /// is equal to it or is a subdomain of it (so weeding `example.com` also weeds
+    /// `blog.example.com`). An empty allow list means "no restriction".
+    pub async fn is_url_allowed(&self, url: &str) -> Result<bool> {
+        let host = match Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_ascii_lowercase)) {
+            Some(host) => host,
+            None => return Ok(false),
+        };
+
+        self.with_conn(move |conn| {
+            let weeded: Vec<String> = conn
+                .prepare("SELECT domain FROM weeded_domains")?
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            if weeded.iter().any(|d| host_matches(&host, d)) {
+                return Ok(false);
+            }
+
+            let allowed: Vec<String> = conn
+                .prepare("SELECT domain FROM allowed_domains")?
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            if allowed.is_empty() {
+                return Ok(true);
+            }
+            Ok(allowed.iter().any(|d| host_matches(&host, d)))
+        })
+        .await
+    }
+
+    /// Increments `spam_count` or `ham_count` for a token (identified by the two i32 halves
+    /// of its 64-bit hash) in the backlink spam-classifier's token table.
+    pub async fn record_spam_token(&self, hash_h1: i32, hash_h2: i32, is_spam: bool) -> Result<()> {
+        self.with_conn(move |conn| {
+            if is_spam {
+                conn.execute(
+                    "INSERT INTO spam_tokens (hash_h1, hash_h2, spam_count, ham_count) VALUES (?1, ?2, 1, 0)
+                     ON CONFLICT(hash_h1, hash_h2) DO UPDATE SET spam_count = spam_count + 1",
+                    params![hash_h1, hash_h2],
+                )?;
+            } else {
+                conn.execute(
+                    "INSERT INTO spam_tokens (hash_h1, hash_h2, spam_count, ham_count) VALUES (?1, ?2, 0, 1)
+                     ON CONFLICT(hash_h1, hash_h2) DO UPDATE SET ham_count = ham_count + 1",
+                    params![hash_h1, hash_h2],
+                )?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Looks up `spam_count`/`ham_count` for a batch of token hashes at once, keyed by the
+    /// same `(hash_h1, hash_h2)` pair used to train them. Tokens with no rows are absent
+    /// from the result rather than returned as zero.
+    pub async fn spam_token_counts(&self, hashes: &[(i32, i32)]) -> Result<HashMap<(i32, i32), (i64, i64)>> {
+        let hashes = hashes.to_vec();
+        self.with_conn(move |conn| {
+            let mut counts = HashMap::new();
+            let mut stmt = conn.prepare(
+                "SELECT spam_count, ham_count FROM spam_tokens WHERE hash_h1 = ?1 AND hash_h2 = ?2",
+            )?;
+            for (h1, h2) in hashes {
+                let row: Option<(i64, i64)> = stmt
+                    .query_row(params![h1, h2], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .optional()?;
+                if let Some(row) = row {
+                    counts.insert((h1, h2), row);
+                }
+            }
+            Ok(counts)
+        })
+        .await
+    }
+
+    /// The total `spam_count` and `ham_count` across every trained token, used to normalize
+    /// per-token spamminess during scoring.
+    pub async fn spam_token_totals(&self) -> Result<(i64, i64)> {
+        self.with_conn(move |conn| {
+            conn.query_row(
+                "SELECT COALESCE(SUM(spam_count), 0), COALESCE(SUM(ham_count), 0) FROM spam_tokens",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(Into::into)
+        })
+        .await
+    }
+
+    /// Retroactively deletes every row in `crawl_results`, `backlinks`, and `seed_urls`
+    /// whose host matches an entry in the weed list.
+    pub async fn purge_weeded_urls(&self) -> Result<()> {
+        self.with_conn(move |conn| {
+            let weeded: Vec<String> = conn
+                .prepare("SELECT domain FROM weeded_domains")?
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+
+            let urls: Vec<String> = conn
+                .prepare("SELECT url FROM crawl_results UNION SELECT source_url FROM backlinks UNION SELECT target_url FROM backlinks UNION SELECT url FROM seed_urls")?
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+
+            for url in urls {
+                let host = match Url::parse(&url).ok().and_then(|u| u.host_str().map(str::to_ascii_lowercase)) {
+                    Some(host) => host,
+                    None => continue,
+                };
+                if weeded.iter().any(|d| host_matches(&host, d)) {
+                    conn.execute("DELETE FROM crawl_results WHERE url = ?1", params![url])?;
+                    conn.execute(
+                        "DELETE FROM backlinks WHERE source_url = ?1 OR target_url = ?1",
+                        params![url],
+                    )?;
+                    conn.execute("DELETE FROM seed_urls WHERE url = ?1", params![url])?;
+                }
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Returns URLs whose stored SimHash fingerprint is within `max_hamming` bits of
+    /// `fingerprint`. Candidates are limited to rows sharing at least one of four
+    /// non-overlapping 16-bit slices ("probe bands") of the fingerprint with `fingerprint`,
+    /// the standard multi-probe SimHash banding scheme: by pigeonhole, any two 64-bit
+    /// fingerprints at Hamming distance 3 or less must agree on at least one of the four
+    /// 16-bit blocks, so `max_hamming <= 3` is guaranteed not to miss a match. Above that,
+    /// recall degrades gradually rather than cutting off sharply the way a single 16-bit
+    /// band would. Either way this only touches the small slice of rows indexed by each band,
+    /// not the whole table.
+    pub async fn find_near_duplicates(&self, fingerprint: i64, max_hamming: u32) -> Result<Vec<String>> {
+        let fp = fingerprint as u64;
+        let band1 = (fp & 0xFFFF) as i64;
+        let band2 = ((fp >> 16) & 0xFFFF) as i64;
+        let band3 = ((fp >> 32) & 0xFFFF) as i64;
+        let band4 = simhash_band(fingerprint);
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT url, simhash FROM crawl_results \
+                 WHERE simhash IS NOT NULL AND ( \
+                     (simhash & 65535) = ?1 OR \
+                     ((simhash >> 16) & 65535) = ?2 OR \
+                     ((simhash >> 32) & 65535) = ?3 OR \
+                     simhash_band = ?4 \
+                 )",
+            )?;
+            let rows = stmt.query_map(params![band1, band2, band3, band4], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?;
+
+            let mut matches = Vec::new();
+            for row in rows {
+                let (url, candidate) = row?;
+                if ((candidate as u64) ^ fp).count_ones() <= max_hamming {
+                    matches.push(url);
+                }
+            }
+            Ok(matches)
+        })
+        .await
+    }
+
+    /// Lists backlinks pointing at `target_url`, using `idx_backlinks_target`.
+    pub async fn get_backlinks_for_target(&self, target_url: &str) -> Result<Vec<BacklinkData>> {
+        let target_url = target_url.to_string();
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT source_url, target_url, anchor_text, context, page_title, domain_authority, is_nofollow, discovered_at
+                FROM backlinks WHERE target_url = ?1
+                "#,
+            )?;
+            let rows = stmt.query_map(params![target_url], |row| {
+                Ok(BacklinkData {
+                    source_url: row.get(0)?,
+                    target_url: row.get(1)?,
+                    anchor_text: row.get(2)?,
+                    context: row.get(3)?,
+                    page_title: row.get(4)?,
+                    domain_authority: row.get(5)?,
+                    is_nofollow: row.get(6)?,
+                    discovered_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
+                        .map_err(|_| rusqlite::Error::InvalidColumnType(7, "discovered_at".to_string(), rusqlite::types::Type::Text))?
+                        .with_timezone(&Utc),
+                })
+            })?;
+
+            let mut results = Vec::new();
+            for row in rows {
+                results.push(row?);
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    pub async fn save_backlinks(&self, backlinks: &[BacklinkData]) -> Result<()> {
+        let backlinks = backlinks.to_vec();
+        let count = backlinks.len();
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                INSERT OR REPLACE INTO backlinks
+                (source_url, target_url, anchor_text, context, page_title, domain_authority, is_nofollow, discovered_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                "#,
+            )?;
+
+            for backlink in &backlinks {
+                stmt.execute(params![
+                    backlink.source_url,
+                    backlink.target_url,
+                    backlink.anchor_text,
+                    backlink.context,
+                    backlink.page_title,
+                    backlink.domain_authority,
+                    backlink.is_nofollow,
+                    backlink.discovered_at.to_rfc3339(),
+                ])?;
+            }
+
+            Ok(())
+        })
+        .await?;
+
+        info!("Saved {} backlinks to database", count);
+        Ok(())
+    }
+
+    /// Persists a url -> PageRank score map into `pagerank_scores`, overwriting any
+    /// previous score for the same url.
+    pub async fn store_pagerank_scores(&self, scores: &HashMap<String, f64>) -> Result<()> {
+        let scores = scores.clone();
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "INSERT OR REPLACE INTO pagerank_scores (url, pagerank_score, last_calculated) VALUES (?1, ?2, ?3)",
+            )?;
+            let now = Utc::now().to_rfc3339();
+            for (url, score) in &scores {
+                stmt.execute(params![url, score, now])?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Runs power-iteration PageRank over the `backlinks` edge list (source_url ->
+    /// target_url, self-loops dropped), persists the result via `store_pagerank_scores`,
+    /// folds each target's score into that row's `domain_authority` column, and returns
+    /// the computed scores. Dangling nodes (no outgoing edges) spread their rank mass
+    /// uniformly across every node each iteration so total rank stays conserved. Returns
+    /// an empty map without touching the database when there are no edges.
+    pub async fn compute_pagerank(&self, damping: f64, max_iters: usize, tolerance: f64) -> Result<HashMap<String, f64>> {
+        let edges = self
+            .with_conn(|conn| {
+                let mut stmt = conn.prepare("SELECT source_url, target_url FROM backlinks")?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?;
+                let mut edges = Vec::new();
+                for row in rows {
+                    edges.push(row?);
+                }
+                Ok(edges)
             })
-        })?;
+            .await?;
+
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for (source, target) in &edges {
+            if source == target {
+                continue;
+            }
+            let next_id = index.len();
+            index.entry(source.clone()).or_insert(next_id);
+            let next_id = index.len();
+            index.entry(target.clone()).or_insert(next_id);
+        }
+        let n = index.len();
+        if n == 0 {
+            return Ok(HashMap::new());
+        }
 
-        let mut seeds = Vec::new();
-        for seed in seed_iter {
-            seeds.push(seed?);
+        let mut out_degree = vec![0usize; n];
+        let mut out_links: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (source, target) in &edges {
+            if source == target {
+                continue;
+            }
+            let from = index[source];
+            let to = index[target];
+            out_degree[from] += 1;
+            out_links[from].push(to);
         }
 
-        Ok(seeds)
+        let mut rank = vec![1.0 / n as f64; n];
+        for _ in 0..max_iters {
+            let dangling_mass: f64 = (0..n)
+                .filter(|&i| out_degree[i] == 0)
+                .map(|i| rank[i])
+                .sum();
+
+            let mut next_rank = vec![(1.0 - damping) / n as f64 + damping * dangling_mass / n as f64; n];
+            for from in 0..n {
+                if out_degree[from] == 0 {
+                    continue;
+                }
+                let share = damping * rank[from] / out_degree[from] as f64;
+                for &to in &out_links[from] {
+                    next_rank[to] += share;
+                }
+            }
+
+            let delta: f64 = rank.iter().zip(next_rank.iter())
+                .map(|(old, new)| (old - new).abs())
+                .sum();
+            rank = next_rank;
+            if delta < tolerance {
+                break;
+            }
+        }
+
+        let scores: HashMap<String, f64> = index.into_iter()
+            .map(|(url, id)| (url, rank[id]))
+            .collect();
+
+        self.store_pagerank_scores(&scores).await?;
+
+        let update_scores = scores.clone();
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare("UPDATE backlinks SET domain_authority = ?1 WHERE target_url = ?2")?;
+            for (url, score) in &update_scores {
+                stmt.execute(params![score, url])?;
+            }
+            Ok(())
+        })
+        .await?;
+
+        Ok(scores)
     }
 
-    pub async fn add_seed_urls(&self, urls: &[String]) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "INSERT OR IGNORE INTO seed_urls (url, added_at, priority) VALUES (?1, ?2, ?3)"
-        )?;
+    /// Looks up the stored `content_hash`/`content_text` for `url`'s last crawl, if any,
+    /// so `save_crawl_result` can tell whether a recrawl actually changed the page.
+    async fn previous_content(&self, url: &str) -> Result<Option<(Option<String>, Option<String>)>> {
+        let url = url.to_string();
+        self.with_conn(move |conn| {
+            conn.query_row(
+                "SELECT content_hash, content_text FROM crawl_results WHERE url = ?1",
+                params![url],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+        })
+        .await
+    }
+
+    /// Bumps `last_seen` for a URL whose recrawl produced an identical `content_hash`,
+    /// without rewriting the stored HTML/text or touching the search index.
+    async fn touch_last_seen(&self, url: &str, seen_at: DateTime<Utc>) -> Result<()> {
+        let url = url.to_string();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "UPDATE crawl_results SET last_seen = ?1 WHERE url = ?2",
+                params![seen_at.to_rfc3339(), url],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Persists a unified diff between a URL's previous and current `content_text`.
+    async fn record_content_change(&self, url: &str, diff: &str, changed_at: DateTime<Utc>) -> Result<()> {
+        let url = url.to_string();
+        let diff = diff.to_string();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO content_changes (url, diff, changed_at) VALUES (?1, ?2, ?3)",
+                params![url, diff, changed_at.to_rfc3339()],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Most recently changed pages, newest first, for the dashboard's "recently changed"
+    /// view.
+    pub async fn get_recent_changes(&self, limit: i32) -> Result<Vec<ContentChange>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT url, diff, changed_at FROM content_changes ORDER BY changed_at DESC LIMIT ?",
+            )?;
+            let rows = stmt.query_map(params![limit], |row| {
+                let changed_at: String = row.get(2)?;
+                Ok(ContentChange {
+                    url: row.get(0)?,
+                    diff: row.get(1)?,
+                    changed_at: DateTime::parse_from_rfc3339(&changed_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                })
+            })?;
+
+            let mut results = Vec::new();
+            for row in rows {
+                results.push(row?);
+            }
+            Ok(results)
+        })
+        .await
+    }
 
+    pub async fn save_crawl_result(&self, result: &CrawlResult) -> Result<()> {
+        if !self.is_url_allowed(&result.url).await? {
+            return Ok(());
+        }
+
+        let mut result = result.clone();
+        if let Some(raw_html) = result.content_html.clone() {
+            let (cleaned_html, sanitized_text, word_count) = self.sanitize_html(&raw_html);
+            result.content_html = Some(cleaned_html);
+            result.content_text = Some(sanitized_text);
+            result.word_count = Some(word_count as i32);
+        }
+
+        let previous = self.previous_content(&result.url).await?;
+        if let Some((Some(prev_hash), _)) = &previous {
+            if Some(prev_hash.as_str()) == result.content_hash.as_deref() {
+                self.touch_last_seen(&result.url, result.crawled_at).await?;
+                return Ok(());
+            }
+        }
+
+        let fingerprint = result.content_text.as_deref().map(simhash);
+        let band = fingerprint.map(simhash_band);
+        self.with_conn({
+            let result = result.clone();
+            move |conn| {
+                conn.execute(
+                    r#"
+                    INSERT OR REPLACE INTO crawl_results
+                    (url, original_url, redirect_chain, title, meta_description, content_text, content_html,
+                     content_hash, word_count, page_size, http_status_code, response_time_ms, language,
+                     charset, h1_tags, h2_tags, meta_keywords, canonical_url, robots_meta,
+                     internal_links_count, external_links_count, images_count, content_type,
+                     file_extension, crawl_success, error_message, crawled_at, simhash, simhash_band, last_seen)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30)
+                    "#,
+                    params![
+                        result.url,
+                        result.original_url,
+                        result.redirect_chain.as_ref().map(|v| serde_json::to_string(v).unwrap_or_default()),
+                        result.title,
+                        result.meta_description,
+                        result.content_text,
+                        result.content_html,
+                        result.content_hash,
+                        result.word_count,
+                        result.page_size,
+                        result.http_status_code,
+                        result.response_time_ms,
+                        result.language,
+                        result.charset,
+                        result.h1_tags.as_ref().map(|v| serde_json::to_string(v).unwrap_or_default()),
+                        result.h2_tags.as_ref().map(|v| serde_json::to_string(v).unwrap_or_default()),
+                        result.meta_keywords.as_ref().map(|v| serde_json::to_string(v).unwrap_or_default()),
+                        result.canonical_url,
+                        result.robots_meta,
+                        result.internal_links_count,
+                        result.external_links_count,
+                        result.images_count,
+                        result.content_type,
+                        result.file_extension,
+                        result.crawl_success,
+                        result.error_message,
+                        result.crawled_at.to_rfc3339(),
+                        fingerprint,
+                        band,
+                        result.crawled_at.to_rfc3339(),
+                    ],
+                )?;
+                Ok(())
+            }
+        })
+        .await?;
+
+        if let Some((_, Some(prev_text))) = &previous {
+            if let Some(new_text) = result.content_text.as_deref() {
+                let diff = diffy::create_patch(prev_text, new_text).to_string();
+                if let Err(e) = self.record_content_change(&result.url, &diff, result.crawled_at).await {
+                    warn!("Failed to record content change for {}: {}", result.url, e);
+                }
+            }
+        }
+
+        if let Err(e) = self.index_crawl_result(&result) {
+            warn!("Failed to index crawl result for search: {}", e);
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_seed_urls(&self, limit: i32) -> Result<Vec<SeedUrl>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT url, added_at, priority, last_crawled, crawl_count
+                 FROM seed_urls
+                 ORDER BY priority DESC, last_crawled ASC NULLS FIRST
+                 LIMIT ?",
+            )?;
+
+            let seed_iter = stmt.query_map(params![limit], |row| {
+                Ok(SeedUrl {
+                    url: row.get(0)?,
+                    added_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
+                        .map_err(|_| rusqlite::Error::InvalidColumnType(1, "added_at".to_string(), rusqlite::types::Type::Text))?
+                        .with_timezone(&Utc),
+                    priority: row.get(2)?,
+                    last_crawled: row.get::<_, Option<String>>(3)?
+                        .map(|s| DateTime::parse_from_rfc3339(&s).ok())
+                        .flatten()
+                        .map(|dt| dt.with_timezone(&Utc)),
+                    crawl_count: row.get(4)?,
+                })
+            })?;
+
+            let mut seeds = Vec::new();
+            for seed in seed_iter {
+                seeds.push(seed?);
+            }
+
+            Ok(seeds)
+        })
+        .await
+    }
+
+    pub async fn add_seed_urls(&self, urls: &[String]) -> Result<()> {
+        let mut allowed_urls = Vec::with_capacity(urls.len());
         for url in urls {
-            stmt.execute(params![
-                url,
-                Utc::now().to_rfc3339(),
-                1
-            ])?;
+            if self.is_url_allowed(url).await? {
+                allowed_urls.push(url.clone());
+            }
         }
+        let count = allowed_urls.len();
+
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "INSERT OR IGNORE INTO seed_urls (url, added_at, priority) VALUES (?1, ?2, ?3)"
+            )?;
 
-        info!("Added {} new seed URLs", urls.len());
+            for url in &allowed_urls {
+                stmt.execute(params![
+                    url,
+                    Utc::now().to_rfc3339(),
+                    1
+                ])?;
+            }
+
+            Ok(())
+        })
+        .await?;
+
+        info!("Added {} new seed URLs", count);
         Ok(())
     }
 
+    /// Deletes every stored `seed_urls` row whose host matches one of `weed_domains`,
+    /// together with the `crawl_results` row for the same URL, so operators can
+    /// retroactively clear already-queued seeds after scoping `CrawlerConfig::weed_domains`
+    /// to a narrower set of sites. Crawl results for URLs that are not seeds are left in
+    /// place. Returns the number of seed URLs removed.
+    pub async fn prune_weeded_seed_urls(&self, weed_domains: &[String]) -> Result<usize> {
+        if weed_domains.is_empty() {
+            return Ok(0);
+        }
+        let weed_domains = weed_domains.to_vec();
+
+        self.with_conn(move |conn| {
+            let urls: Vec<String> = conn
+                .prepare("SELECT url FROM seed_urls")?
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+
+            let mut removed = 0;
+            for url in urls {
+                let Some(host) = Url::parse(&url).ok().and_then(|u| u.host_str().map(str::to_ascii_lowercase)) else {
+                    continue;
+                };
+                if weed_domains.iter().any(|d| host_matches(&host, d)) {
+                    conn.execute("DELETE FROM seed_urls WHERE url = ?1", params![url])?;
+                    conn.execute("DELETE FROM crawl_results WHERE url = ?1", params![url])?;
+                    removed += 1;
+                }
+            }
+
+            Ok(removed)
+        })
+        .await
+    }
+
     pub async fn update_seed_url_crawled(&self, url: &str) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "UPDATE seed_urls SET last_crawled = ?, crawl_count = crawl_count + 1 WHERE url = ?",
-            params![Utc::now().to_rfc3339(), url],
-        )?;
-        Ok(())
+        let url = url.to_string();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "UPDATE seed_urls SET last_crawled = ?, crawl_count = crawl_count + 1 WHERE url = ?",
+                params![Utc::now().to_rfc3339(), url],
+            )?;
+            Ok(())
+        })
+        .await
     }
 
     pub async fn get_dashboard_stats(&self) -> Result<DashboardStats> {
-        let conn = self.conn.lock().unwrap();
+        self.with_conn(|conn| {
 
         // Get basic counts
         let total_urls_crawled: i64 = conn.query_row(
@@ -349,101 +1334,180 @@ impl Database {
             current_mode,
             next_mode_switch,
         })
+        })
+        .await
+    }
+
+    /// Total crawl attempts recorded as failed, for the `/metrics` error counter.
+    pub async fn get_error_count(&self) -> Result<i64> {
+        self.with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT COUNT(*) FROM crawl_results WHERE crawl_success = 0",
+                [],
+                |row| row.get(0),
+            )?)
+        })
+        .await
     }
 
     pub async fn update_stats(&self, stats: &DashboardStats) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            r#"
-            UPDATE stats SET
-                total_urls_crawled = ?,
-                total_backlinks_found = ?,
-                unique_domains = ?,
-                crawl_rate_per_hour = ?,
-                backlink_rate_per_hour = ?,
-                database_size_mb = ?,
-                system_memory_usage = ?,
-                system_cpu_usage = ?,
-                last_updated = ?,
-                current_mode = ?,
-                next_mode_switch = ?
-            WHERE id = 1
-            "#,
-            params![
-                stats.total_urls_crawled,
-                stats.total_backlinks_found,
-                stats.unique_domains,
-                stats.crawl_rate_per_hour,
-                stats.backlink_rate_per_hour,
-                stats.database_size_mb,
-                stats.system_memory_usage,
-                stats.system_cpu_usage,
-                stats.last_updated.to_rfc3339(),
-                stats.current_mode,
-                stats.next_mode_switch.to_rfc3339(),
-            ],
-        )?;
-        Ok(())
+        let stats = stats.clone();
+        self.with_conn(move |conn| {
+            conn.execute(
+                r#"
+                UPDATE stats SET
+                    total_urls_crawled = ?,
+                    total_backlinks_found = ?,
+                    unique_domains = ?,
+                    crawl_rate_per_hour = ?,
+                    backlink_rate_per_hour = ?,
+                    database_size_mb = ?,
+                    system_memory_usage = ?,
+                    system_cpu_usage = ?,
+                    last_updated = ?,
+                    current_mode = ?,
+                    next_mode_switch = ?
+                WHERE id = 1
+                "#,
+                params![
+                    stats.total_urls_crawled,
+                    stats.total_backlinks_found,
+                    stats.unique_domains,
+                    stats.crawl_rate_per_hour,
+                    stats.backlink_rate_per_hour,
+                    stats.database_size_mb,
+                    stats.system_memory_usage,
+                    stats.system_cpu_usage,
+                    stats.last_updated.to_rfc3339(),
+                    stats.current_mode,
+                    stats.next_mode_switch.to_rfc3339(),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
     }
 
     pub async fn get_recent_crawls(&self, limit: i32) -> Result<Vec<CrawlResult>> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT url, original_url, redirect_chain, title, meta_description, content_text, content_html,
-                   content_hash, word_count, page_size, http_status_code, response_time_ms, language,
-                   charset, h1_tags, h2_tags, meta_keywords, canonical_url, robots_meta,
-                   internal_links_count, external_links_count, images_count, content_type,
-                   file_extension, crawl_success, error_message, crawled_at
-            FROM crawl_results
-            ORDER BY crawled_at DESC
-            LIMIT ?
-            "#,
-        )?;
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT url, original_url, redirect_chain, title, meta_description, content_text, content_html,
+                       content_hash, word_count, page_size, http_status_code, response_time_ms, language,
+                       charset, h1_tags, h2_tags, meta_keywords, canonical_url, robots_meta,
+                       internal_links_count, external_links_count, images_count, content_type,
+                       file_extension, crawl_success, error_message, crawled_at
+                FROM crawl_results
+                ORDER BY crawled_at DESC
+                LIMIT ?
+                "#,
+            )?;
 
-        let crawl_iter = stmt.query_map(params![limit], |row| {
-            Ok(CrawlResult {
-                url: row.get(0)?,
-                original_url: row.get(1)?,
-                redirect_chain: row.get::<_, Option<String>>(2)?
-                    .and_then(|s| serde_json::from_str(&s).ok()),
-                title: row.get(3)?,
-                meta_description: row.get(4)?,
-                content_text: row.get(5)?,
-                content_html: row.get(6)?,
-                content_hash: row.get(7)?,
-                word_count: row.get(8)?,
-                page_size: row.get(9)?,
-                http_status_code: row.get(10)?,
-                response_time_ms: row.get(11)?,
-                language: row.get(12)?,
-                charset: row.get(13)?,
-                h1_tags: row.get::<_, Option<String>>(14)?
-                    .and_then(|s| serde_json::from_str(&s).ok()),
-                h2_tags: row.get::<_, Option<String>>(15)?
-                    .and_then(|s| serde_json::from_str(&s).ok()),
-                meta_keywords: row.get::<_, Option<String>>(16)?
-                    .and_then(|s| serde_json::from_str(&s).ok()),
-                canonical_url: row.get(17)?,
-                robots_meta: row.get(18)?,
-                internal_links_count: row.get(19)?,
-                external_links_count: row.get(20)?,
-                images_count: row.get(21)?,
-                content_type: row.get(22)?,
-                file_extension: row.get(23)?,
-                crawl_success: row.get(24)?,
-                error_message: row.get(25)?,
-                crawled_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(26)?)
-                    .map_err(|_| rusqlite::Error::InvalidColumnType(26, "crawled_at".to_string(), rusqlite::types::Type::Text))?
-                    .with_timezone(&Utc),
-            })
-        })?;
+            let crawl_iter = stmt.query_map(params![limit], Self::row_to_crawl_result)?;
 
-        let mut results = Vec::new();
-        for result in crawl_iter {
-            results.push(result?);
-        }
+            let mut results = Vec::new();
+            for result in crawl_iter {
+                results.push(result?);
+            }
 
-        Ok(results)
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Logs the start of a `run_backlink_processing`/`run_web_crawling` session and returns
+    /// its row id, to be passed to `complete_processing_session` once it finishes.
+    pub async fn start_processing_session(&self, session_type: &str) -> Result<i64> {
+        let session_type = session_type.to_string();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO processing_sessions (session_type, start_time, status) VALUES (?1, ?2, 'running')",
+                params![session_type, Utc::now().to_rfc3339()],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+        .await
+    }
+
+    /// Marks a processing session as finished, recording how much it did and whether it
+    /// succeeded.
+    pub async fn complete_processing_session(
+        &self,
+        id: i64,
+        items_processed: i32,
+        errors: i32,
+        status: &str,
+    ) -> Result<()> {
+        let status = status.to_string();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "UPDATE processing_sessions SET end_time = ?1, items_processed = ?2, errors = ?3, status = ?4 WHERE id = ?5",
+                params![Utc::now().to_rfc3339(), items_processed, errors, status, id],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Lists past processing sessions, most recent first, optionally filtered by
+    /// `session_type`/`status` and paginated via `limit`/`offset`.
+    pub async fn list_processing_sessions(
+        &self,
+        session_type: Option<String>,
+        status: Option<String>,
+        limit: i32,
+        offset: i32,
+    ) -> Result<Vec<ProcessingSession>> {
+        self.with_conn(move |conn| {
+            let mut query = String::from(
+                "SELECT id, session_type, start_time, end_time, items_processed, errors, status
+                 FROM processing_sessions WHERE 1=1",
+            );
+            if session_type.is_some() {
+                query.push_str(" AND session_type = ?");
+            }
+            if status.is_some() {
+                query.push_str(" AND status = ?");
+            }
+            query.push_str(" ORDER BY start_time DESC LIMIT ? OFFSET ?");
+
+            let mut stmt = conn.prepare(&query)?;
+            let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+            if let Some(t) = &session_type {
+                param_values.push(Box::new(t.clone()));
+            }
+            if let Some(s) = &status {
+                param_values.push(Box::new(s.clone()));
+            }
+            param_values.push(Box::new(limit));
+            param_values.push(Box::new(offset));
+
+            let params_ref: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|p| p.as_ref()).collect();
+
+            let rows = stmt.query_map(params_ref.as_slice(), |row| {
+                let start_time: String = row.get(2)?;
+                let end_time: Option<String> = row.get(3)?;
+                Ok(ProcessingSession {
+                    id: row.get(0)?,
+                    session_type: row.get(1)?,
+                    start_time: DateTime::parse_from_rfc3339(&start_time)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    end_time: end_time
+                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                        .map(|dt| dt.with_timezone(&Utc)),
+                    items_processed: row.get(4)?,
+                    errors: row.get(5)?,
+                    status: row.get(6)?,
+                })
+            })?;
+
+            let mut sessions = Vec::new();
+            for row in rows {
+                sessions.push(row?);
+            }
+            Ok(sessions)
+        })
+        .await
     }
 }
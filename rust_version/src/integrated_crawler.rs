@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 use tokio::time::timeout;
 use crate::models::*;
@@ -7,6 +7,94 @@ use crate::backlink_processor::BacklinkProcessor;
 use crate::database::Database;
 use std::sync::Arc;
 use anyhow::Result;
+use chrono::{DateTime, Utc, Datelike, Timelike};
+use url::Url;
+
+/// Damping factor for the PageRank power iteration, matching the standard value from the
+/// original PageRank paper.
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_MAX_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f64 = 1e-6;
+
+/// Returns `url`'s registrable domain (eTLD+1, e.g. `www.example.co.uk` -> `example.co.uk`)
+/// via the public suffix list, falling back to the bare host if it can't be parsed.
+fn reduced_domain(url: &str) -> String {
+    let host = Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_string()))
+        .unwrap_or_default();
+    psl::List
+        .domain(host.as_bytes())
+        .map(|d| String::from_utf8_lossy(d.as_bytes()).to_string())
+        .unwrap_or(host)
+}
+
+/// Runs power-iteration PageRank over the directed graph formed by `backlinks` (an edge per
+/// backlink, from its source page to its target page), following the standard recurrence
+/// `PR(u) = (1-d)/N + d * Σ_{v→u} PR(v)/outdeg(v)` with dangling-node mass redistributed
+/// uniformly each iteration. Returns one score per URL seen as either a source or a target.
+fn compute_pagerank(backlinks: &[BacklinkData]) -> HashMap<String, f64> {
+    let mut nodes: Vec<String> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for link in backlinks {
+        for url in [&link.source_url, &link.target_url] {
+            index.entry(url.clone()).or_insert_with(|| {
+                nodes.push(url.clone());
+                nodes.len() - 1
+            });
+        }
+    }
+
+    let n = nodes.len();
+    if n == 0 {
+        return HashMap::new();
+    }
+
+    let mut out_edges: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for link in backlinks {
+        out_edges[index[&link.source_url]].push(index[&link.target_url]);
+    }
+    let out_degree: Vec<usize> = out_edges.iter().map(Vec::len).collect();
+
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..PAGERANK_MAX_ITERATIONS {
+        let dangling_mass: f64 = (0..n).filter(|&i| out_degree[i] == 0).map(|i| rank[i]).sum();
+        let base = (1.0 - PAGERANK_DAMPING) / n as f64 + PAGERANK_DAMPING * dangling_mass / n as f64;
+        let mut next = vec![base; n];
+
+        for (from, targets) in out_edges.iter().enumerate() {
+            if targets.is_empty() {
+                continue;
+            }
+            let share = PAGERANK_DAMPING * rank[from] / targets.len() as f64;
+            for &to in targets {
+                next[to] += share;
+            }
+        }
+
+        let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+
+    nodes.into_iter().zip(rank).collect()
+}
+
+/// Aggregates per-URL PageRank mass by registrable domain and log-scales it so domain
+/// authority grows slowly with accumulated rank instead of linearly.
+fn domain_authority_scores(pagerank: &HashMap<String, f64>) -> HashMap<String, f64> {
+    let n = pagerank.len() as f64;
+    let mut mass_by_domain: HashMap<String, f64> = HashMap::new();
+    for (url, score) in pagerank {
+        *mass_by_domain.entry(reduced_domain(url)).or_insert(0.0) += score;
+    }
+    mass_by_domain
+        .into_iter()
+        .map(|(domain, mass)| (domain, (mass * n).ln_1p()))
+        .collect()
+}
 
 pub struct IntegratedCrawler {
     web_crawler: WebsiteCrawler,
@@ -31,6 +119,20 @@ impl IntegratedCrawler {
             user_agents: vec![config.backlink_config.user_agent.clone()],
             max_depth: config.web_crawl_config.max_depth as u32,
             enable_javascript: false,
+            allowed_domains: Vec::new(),
+            weed_domains: Vec::new(),
+            proxies: Vec::new(),
+            requests_per_second_per_domain: 1.0,
+            burst: 3,
+            max_requests_per_host_per_second: 1.0,
+            max_redirects: 10,
+            max_body_bytes: 4 * 1024 * 1024,
+            max_fetch_duration_secs: 10,
+            min_language_confidence: 0.7,
+            page_budget: config.web_crawl_config.page_budget,
+            links_per_page_budget: config.web_crawl_config.links_per_page_budget,
+            max_level: config.web_crawl_config.max_level,
+            accepted_content_types: config.web_crawl_config.accepted_content_types.clone(),
         };
 
         let web_crawler = WebsiteCrawler::new(&config.web_crawl_config);
@@ -64,7 +166,7 @@ impl IntegratedCrawler {
 
         // Step 2: Analyze backlinks for each crawled page
         println!("Step 2: Analyzing backlinks...");
-        let mut backlink_results = Vec::new();
+        let mut per_url_backlinks: Vec<(String, Vec<BacklinkData>)> = Vec::new();
         let crawled_urls: Vec<String> = Vec::new(); // TODO: Implement get_all_crawled_urls
 
         for (i, url) in crawled_urls.iter().enumerate() {
@@ -78,16 +180,9 @@ impl IntegratedCrawler {
             let analysis = self.backlink_processor.discover_backlinks_for_url(url, 3).await;
             match analysis {
                 Ok(backlinks) => {
-                    println!("  Found {} backlinks from {} unique domains",
-                             backlinks.len(), 42); // TODO: Calculate unique domains
-                    let analysis = BacklinkAnalysis {
-                        total_backlinks: backlinks.len(),
-                        unique_domains: 42, // TODO: Calculate unique domains
-                        spam_backlinks: 0,
-                        domain_authority: 0.0,
-                        pagerank_score: 0.0,
-                    };
-                    backlink_results.push((url.clone(), analysis));
+                    let unique_domains = backlinks.iter().map(|b| reduced_domain(&b.source_url)).collect::<HashSet<_>>().len();
+                    println!("  Found {} backlinks from {} unique domains", backlinks.len(), unique_domains);
+                    per_url_backlinks.push((url.clone(), backlinks));
                 }
                 Err(e) => {
                     println!("  Error analyzing backlinks for {}: {:?}", url, e);
@@ -98,6 +193,28 @@ impl IntegratedCrawler {
             tokio::time::sleep(Duration::from_millis(500)).await;
         }
 
+        // PageRank and domain authority are computed once over every backlink edge
+        // discovered this session, then looked up per page below.
+        let all_backlinks: Vec<BacklinkData> = per_url_backlinks.iter().flat_map(|(_, b)| b.clone()).collect();
+        let pagerank = compute_pagerank(&all_backlinks);
+        let domain_authority = domain_authority_scores(&pagerank);
+
+        let backlink_results: Vec<(String, BacklinkAnalysis)> = per_url_backlinks
+            .into_iter()
+            .map(|(url, backlinks)| {
+                let unique_domains = backlinks.iter().map(|b| reduced_domain(&b.source_url)).collect::<HashSet<_>>().len();
+                let analysis = BacklinkAnalysis {
+                    total_backlinks: backlinks.len(),
+                    unique_domains,
+                    spam_backlinks: 0,
+                    malicious_backlinks: 0,
+                    domain_authority: domain_authority.get(&reduced_domain(&url)).copied().unwrap_or(0.0),
+                    pagerank_score: pagerank.get(&url).copied().unwrap_or(0.0),
+                };
+                (url, analysis)
+            })
+            .collect();
+
         // Step 3: Generate comprehensive report
         let report = self.generate_crawl_report(&crawl_result, &backlink_results)?;
 
@@ -113,29 +230,40 @@ impl IntegratedCrawler {
     }
 
     pub async fn analyze_backlinks_only(&mut self, target_urls: Vec<String>) -> Result<Vec<(String, BacklinkAnalysis)>, CrawlError> {
-        let mut results = Vec::new();
+        let mut per_url_backlinks: Vec<(String, Vec<BacklinkData>)> = Vec::new();
 
         for url in target_urls {
             println!("Analyzing backlinks for: {}", url);
 
             let analysis = self.backlink_processor.discover_backlinks_for_url(&url, 3).await;
             match analysis {
-                Ok(backlinks) => {
-                    let analysis = BacklinkAnalysis {
-                        total_backlinks: backlinks.len(),
-                        unique_domains: 42, // TODO: Calculate unique domains
-                        spam_backlinks: 0,
-                        domain_authority: 0.0,
-                        pagerank_score: 0.0,
-                    };
-                    results.push((url, analysis));
-                }
+                Ok(backlinks) => per_url_backlinks.push((url, backlinks)),
                 Err(e) => {
                     println!("Error analyzing backlinks for {}: {:?}", url, e);
                 }
             }
         }
 
+        let all_backlinks: Vec<BacklinkData> = per_url_backlinks.iter().flat_map(|(_, b)| b.clone()).collect();
+        let pagerank = compute_pagerank(&all_backlinks);
+        let domain_authority = domain_authority_scores(&pagerank);
+
+        let results = per_url_backlinks
+            .into_iter()
+            .map(|(url, backlinks)| {
+                let unique_domains = backlinks.iter().map(|b| reduced_domain(&b.source_url)).collect::<HashSet<_>>().len();
+                let analysis = BacklinkAnalysis {
+                    total_backlinks: backlinks.len(),
+                    unique_domains,
+                    spam_backlinks: 0,
+                    malicious_backlinks: 0,
+                    domain_authority: domain_authority.get(&reduced_domain(&url)).copied().unwrap_or(0.0),
+                    pagerank_score: pagerank.get(&url).copied().unwrap_or(0.0),
+                };
+                (url, analysis)
+            })
+            .collect();
+
         Ok(results)
     }
 
@@ -143,13 +271,29 @@ impl IntegratedCrawler {
         println!("Starting domain analysis for: {}", domain);
 
         // Generate seed URLs for the domain
-        let seed_urls = vec![
+        let mut seed_urls = vec![
             format!("https://{}", domain),
             format!("https://{}/", domain),
             format!("http://{}", domain),
             format!("http://{}/", domain),
         ];
 
+        // Broaden coverage beyond the handful of root variants above: fetch robots.txt for
+        // its `Sitemap:` directives (plus the conventional `/sitemap.xml`), recursively expand
+        // any sitemap-index files, and merge the discovered URLs into the seed set. This
+        // matters directly for the backlink/PageRank analysis that follows, since it only
+        // ever sees pages the crawl actually visits.
+        let sitemap_seed_urls = self.web_crawler.discover_seed_urls_from_sitemaps(&seed_urls).await;
+        if !sitemap_seed_urls.is_empty() {
+            println!("Discovered {} sitemap seed URL(s) for {}", sitemap_seed_urls.len(), domain);
+            let mut seen: HashSet<String> = seed_urls.iter().cloned().collect();
+            for url in sitemap_seed_urls {
+                if seen.insert(url.clone()) {
+                    seed_urls.push(url);
+                }
+            }
+        }
+
         // Crawl the domain
         let crawl_result = timeout(
             Duration::from_secs(self.config.web_crawl_config.timeout_secs * 5),
@@ -162,22 +306,27 @@ impl IntegratedCrawler {
         let backlinks = self.backlink_processor.discover_backlinks_for_url(&main_url, 3).await
             .map_err(|e| CrawlError::DatabaseError(e.to_string()))?;
 
+        let pagerank = compute_pagerank(&backlinks);
+        let unique_domains = backlinks.iter().map(|b| reduced_domain(&b.source_url)).collect::<HashSet<_>>().len();
+
+        // Get domain authority scores
+        let domain_scores = self.get_domain_authority_scores(&backlinks)?;
+        let authority = domain_scores.get(domain).copied().unwrap_or(0.0);
+
         let backlink_analysis = BacklinkAnalysis {
             total_backlinks: backlinks.len(),
-            unique_domains: 42, // TODO: Calculate unique domains
+            unique_domains,
             spam_backlinks: 0,
-            domain_authority: 0.0,
-            pagerank_score: 0.0,
+            malicious_backlinks: 0,
+            domain_authority: authority,
+            pagerank_score: pagerank.get(&main_url).copied().unwrap_or(0.0),
         };
 
-        // Get domain authority scores
-        let domain_scores = self.get_domain_authority_scores()?;
-
         Ok(DomainAnalysis {
             domain: domain.to_string(),
             pages_crawled: crawl_result.pages_crawled.unwrap_or(0),
             backlink_analysis,
-            domain_authority: domain_scores.get(domain).copied().unwrap_or(0.0),
+            domain_authority: authority,
             crawl_errors: crawl_result.errors.unwrap_or(0),
         })
     }
@@ -216,10 +365,11 @@ impl IntegratedCrawler {
         })
     }
 
-    fn get_domain_authority_scores(&self) -> Result<HashMap<String, f64>, CrawlError> {
-        // This would query the database for stored domain scores
-        // For now, return empty map
-        Ok(HashMap::new())
+    /// Derives per-domain authority scores by running PageRank over `backlinks` and
+    /// log-scaling the rank mass aggregated per registrable domain.
+    fn get_domain_authority_scores(&self, backlinks: &[BacklinkData]) -> Result<HashMap<String, f64>, CrawlError> {
+        let pagerank = compute_pagerank(backlinks);
+        Ok(domain_authority_scores(&pagerank))
     }
 
     pub fn get_crawl_statistics(&self) -> Result<CrawlStatistics, CrawlError> {
@@ -246,6 +396,9 @@ impl IntegratedCrawler {
 pub struct CrawlScheduler {
     crawler: IntegratedCrawler,
     schedule: Vec<ScheduledCrawl>,
+    /// When this scheduler started running, used as the `is_due` walk's starting point for
+    /// crawls that have never run.
+    system_start_time: DateTime<Utc>,
 }
 
 impl CrawlScheduler {
@@ -253,6 +406,7 @@ impl CrawlScheduler {
         Self {
             crawler,
             schedule: Vec::new(),
+            system_start_time: Utc::now(),
         }
     }
 
@@ -261,33 +415,112 @@ impl CrawlScheduler {
     }
 
     pub async fn run_scheduled_crawls(&mut self) -> Result<(), CrawlError> {
-        for scheduled in &self.schedule {
-            if scheduled.is_due() {
+        let system_start_time = self.system_start_time;
+        for scheduled in &mut self.schedule {
+            if scheduled.is_due(system_start_time) {
                 println!("Running scheduled crawl: {}", scheduled.name);
-                let _ = self.crawler.crawl_website_with_backlinks(scheduled.urls.clone()).await?;
-                // Mark as completed (would need to update schedule)
+                match self.crawler.crawl_website_with_backlinks(scheduled.urls.clone()).await {
+                    Ok(result) => {
+                        let produced_results = result.web_crawl_result.pages_crawled.unwrap_or(0) > 0
+                            || !result.backlink_analyses.is_empty();
+                        scheduled.record_crawl_outcome(produced_results);
+                    }
+                    Err(e) => {
+                        log::warn!("Scheduled crawl '{}' failed: {}", scheduled.name, e);
+                        scheduled.record_crawl_outcome(false);
+                    }
+                }
+                scheduled.last_run = Some(Utc::now());
             }
         }
         Ok(())
     }
 }
 
+/// Baseline wait between retries of a target that just failed or produced nothing, before
+/// `ScheduledCrawl::backoff_duration`'s exponential growth kicks in.
+const BACKOFF_BASE_INTERVAL_SECS: i64 = 60 * 60;
+/// Hard ceiling on how far backoff can push out a retry, so a permanently dead domain still
+/// gets rechecked occasionally rather than being abandoned forever.
+const BACKOFF_MAX_SECS: i64 = 7 * 24 * 60 * 60;
+/// Beyond this many consecutive failures, the backoff multiplier stops growing — it's
+/// already at (or past) `BACKOFF_MAX_SECS` by then.
+const BACKOFF_MAX_EXPONENT: u32 = 10;
+
 #[derive(Debug, Clone)]
 pub struct ScheduledCrawl {
     pub name: String,
     pub urls: Vec<String>,
     pub schedule: CrawlSchedule,
     pub last_run: Option<chrono::DateTime<chrono::Utc>>,
+    /// Consecutive runs that errored or crawled/discovered nothing new. Drives the
+    /// exponential backoff `is_due` enforces on top of `schedule`, following the
+    /// fediverse.space approach of checking down/unresponsive targets far less often.
+    pub consecutive_failures: u32,
 }
 
 impl ScheduledCrawl {
-    pub fn is_due(&self) -> bool {
-        // Simple implementation - always due for now
-        // Would need proper scheduling logic
-        true
+    /// True once the cron-equivalent of `self.schedule` has a fire time at or before now
+    /// that falls strictly after `last_run` (or `system_start_time`, for a crawl that has
+    /// never run) AND, when `consecutive_failures` is non-zero, at least `backoff_duration`
+    /// has passed since `last_run` (a run with no failure streak adds no wait). Walks forward
+    /// minute-by-minute from that point rather than doing field arithmetic directly, since a
+    /// `Custom` cron expression can combine fields in ways (e.g. "last Friday of the month")
+    /// that aren't solvable in closed form.
+    pub fn is_due(&self, system_start_time: DateTime<Utc>) -> bool {
+        let Some(cron) = CronSchedule::parse(&self.schedule.cron_expr()) else {
+            warn_bad_cron(&self.name, &self.schedule);
+            return false;
+        };
+
+        let earliest = match self.last_run {
+            Some(last_run) if self.consecutive_failures > 0 => last_run + self.backoff_duration(),
+            Some(last_run) => last_run,
+            None => system_start_time,
+        };
+        let mut candidate = match (earliest + chrono::Duration::minutes(1))
+            .with_second(0)
+            .and_then(|dt| dt.with_nanosecond(0))
+        {
+            Some(dt) => dt,
+            None => return false,
+        };
+
+        // Two years of minutes is far more than any valid cron expression needs to find its
+        // next match; it just keeps a malformed one (e.g. day 31 in a "30 day" month-only
+        // spec) from spinning forever.
+        const MAX_MINUTES_CHECKED: i64 = 2 * 365 * 24 * 60;
+        for _ in 0..MAX_MINUTES_CHECKED {
+            if cron.matches(&candidate) {
+                return Utc::now() >= candidate;
+            }
+            candidate += chrono::Duration::minutes(1);
+        }
+        false
+    }
+
+    /// `BACKOFF_BASE_INTERVAL_SECS * 2^min(consecutive_failures, BACKOFF_MAX_EXPONENT)`,
+    /// capped at `BACKOFF_MAX_SECS`.
+    fn backoff_duration(&self) -> chrono::Duration {
+        let exponent = self.consecutive_failures.min(BACKOFF_MAX_EXPONENT);
+        let secs = BACKOFF_BASE_INTERVAL_SECS.saturating_mul(1i64 << exponent);
+        chrono::Duration::seconds(secs.min(BACKOFF_MAX_SECS))
+    }
+
+    /// Resets the backoff streak on a successful, non-empty crawl; otherwise extends it.
+    pub fn record_crawl_outcome(&mut self, produced_results: bool) {
+        if produced_results {
+            self.consecutive_failures = 0;
+        } else {
+            self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        }
     }
 }
 
+fn warn_bad_cron(name: &str, schedule: &CrawlSchedule) {
+    log::warn!("Scheduled crawl '{}' has an unparseable cron expression: {:?}", name, schedule);
+}
+
 #[derive(Debug, Clone)]
 pub enum CrawlSchedule {
     Daily,
@@ -295,3 +528,92 @@ pub enum CrawlSchedule {
     Monthly,
     Custom(String), // Cron expression
 }
+
+impl CrawlSchedule {
+    /// The five-field cron expression equivalent to this schedule: midnight daily, midnight
+    /// Sunday weekly, midnight on the 1st monthly, or the user's own expression for `Custom`.
+    fn cron_expr(&self) -> std::borrow::Cow<'_, str> {
+        match self {
+            CrawlSchedule::Daily => "0 0 * * *".into(),
+            CrawlSchedule::Weekly => "0 0 * * 0".into(),
+            CrawlSchedule::Monthly => "0 0 1 * *".into(),
+            CrawlSchedule::Custom(expr) => expr.as_str().into(),
+        }
+    }
+}
+
+/// A five-field cron expression (`minute hour day-of-month month day-of-week`), parsed into
+/// the set of values each field allows. Supports `*`, `a-b` ranges, `*/n` steps, and
+/// comma-separated lists.
+struct CronSchedule {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Vec<u32>,
+    months: Vec<u32>,
+    /// 0 = Sunday, matching cron convention (and `Weekday::num_days_from_sunday`).
+    days_of_week: Vec<u32>,
+}
+
+impl CronSchedule {
+    fn parse(expr: &str) -> Option<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return None;
+        }
+        Some(Self {
+            minutes: parse_cron_field(fields[0], 0, 59)?,
+            hours: parse_cron_field(fields[1], 0, 23)?,
+            days_of_month: parse_cron_field(fields[2], 1, 31)?,
+            months: parse_cron_field(fields[3], 1, 12)?,
+            days_of_week: parse_cron_field(fields[4], 0, 6)?,
+        })
+    }
+
+    fn matches(&self, dt: &DateTime<Utc>) -> bool {
+        self.minutes.contains(&dt.minute())
+            && self.hours.contains(&dt.hour())
+            && self.days_of_month.contains(&dt.day())
+            && self.months.contains(&dt.month())
+            && self.days_of_week.contains(&dt.weekday().num_days_from_sunday())
+    }
+}
+
+/// Parses one cron field (`*`, `*/n`, `a-b`, or a comma-separated mix of those and bare
+/// numbers) into the sorted set of values it allows. Returns `None` if any part does not
+/// parse, a step is `0`, or a value or range falls outside `[min, max]`.
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Option<Vec<u32>> {
+    let mut values = std::collections::BTreeSet::new();
+    for part in field.split(',') {
+        if part == "*" {
+            values.extend(min..=max);
+        } else if let Some(step_str) = part.strip_prefix("*/") {
+            let step: u32 = step_str.parse().ok()?;
+            if step == 0 {
+                return None;
+            }
+            let mut v = min;
+            while v <= max {
+                values.insert(v);
+                v += step;
+            }
+        } else if let Some((start, end)) = part.split_once('-') {
+            let start: u32 = start.parse().ok()?;
+            let end: u32 = end.parse().ok()?;
+            if start > end || start < min || end > max {
+                return None;
+            }
+            values.extend(start..=end);
+        } else {
+            let v: u32 = part.parse().ok()?;
+            if v < min || v > max {
+                return None;
+            }
+            values.insert(v);
+        }
+    }
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.into_iter().collect())
+    }
+}
@@ -1,15 +1,21 @@
 pub mod models;
 pub mod database;
+pub mod database_new;
 pub mod backlink_processor;
 pub mod crawler;
 pub mod integrated_crawler;
 pub mod scheduler;
 pub mod dashboard;
+pub mod api;
+pub mod cache;
 
 pub use models::*;
 pub use database::*;
+pub use database_new::Database;
 pub use backlink_processor::*;
 pub use crawler::*;
 pub use integrated_crawler::*;
 pub use scheduler::*;
 pub use dashboard::*;
+pub use api::*;
+pub use cache::*;
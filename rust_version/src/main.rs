@@ -4,10 +4,13 @@ use log::info;
 
 mod models;
 mod database;
+mod database_new;
 mod backlink_processor;
 mod crawler;
 mod scheduler;
 mod dashboard;
+mod api;
+mod cache;
 mod main_app;
 
 use main_app::MainApplication;
@@ -4,20 +4,26 @@ use crate::backlink_processor::BacklinkProcessor;
 use crate::database::Database;
 use crate::dashboard::DashboardServer;
 use crate::crawler::WebsiteCrawler;
+use crate::cache::{CacheConfig, CacheEntry, UrlCache};
 use anyhow::Result;
 use chrono::Utc;
+use futures::FutureExt;
 use log::{info, warn, error};
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::{interval, sleep};
 use serde_json;
+use sysinfo::{System, SystemExt, CpuExt};
 
 pub struct MainApplication {
     database: Arc<Database>,
-    scheduler: ScheduleManager,
+    scheduler: Arc<ScheduleManager>,
     backlink_processor: BacklinkProcessor,
     crawler: WebsiteCrawler,
     dashboard_server: DashboardServer,
+    url_cache: UrlCache,
     config: MainApplicationConfig,
 }
 
@@ -28,6 +34,7 @@ pub struct MainApplicationConfig {
     pub check_interval_minutes: u64,
     pub crawler_config: CrawlerConfig,
     pub web_crawl_config: CrawlConfig,
+    pub cache_config: CacheConfig,
 }
 
 impl Default for MainApplicationConfig {
@@ -38,6 +45,7 @@ impl Default for MainApplicationConfig {
             check_interval_minutes: 10,
             crawler_config: CrawlerConfig::default(),
             web_crawl_config: CrawlConfig::default(),
+            cache_config: CacheConfig::default(),
         }
     }
 }
@@ -56,8 +64,10 @@ impl MainApplication {
             crawling_hours: (0..24).filter(|h| ![6, 7, 12, 13, 18, 19, 0, 1].contains(h)).collect(),
             timezone: "UTC".to_string(),
             session_duration_hours: 2,
+            metrics_port: None,
+            heartbeat_url: None,
         };
-        let scheduler = ScheduleManager::new(schedule_config);
+        let scheduler = Arc::new(ScheduleManager::new(schedule_config));
         info!("✅ Scheduler initialized");
 
         // Initialize backlink processor
@@ -72,12 +82,17 @@ impl MainApplication {
         let dashboard_server = DashboardServer::new(database.clone(), config.dashboard_port);
         info!("✅ Dashboard server initialized on port {}", config.dashboard_port);
 
+        // Initialize the on-disk URL cache used to skip re-processing unchanged pages
+        let url_cache = UrlCache::new(&config.cache_config)?;
+        info!("✅ URL cache initialized at {}", config.cache_config.directory);
+
         Ok(Self {
             database,
             scheduler,
             backlink_processor,
             crawler,
             dashboard_server,
+            url_cache,
             config,
         })
     }
@@ -109,12 +124,25 @@ impl MainApplication {
 
         info!("🌐 Dashboard available at: http://localhost:{}", self.config.dashboard_port);
 
+        // Install SIGINT/SIGTERM handlers so the processing loop finishes its current
+        // cycle and exits cleanly instead of being killed outright.
+        self.scheduler.install_signal_handlers();
+
+        // Start scheduler metrics server in background (no-op if metrics_port is unset)
+        let scheduler_for_metrics = self.scheduler.clone();
+        let metrics_task = tokio::spawn(async move {
+            if let Err(e) = scheduler_for_metrics.start_metrics_server().await {
+                error!("Scheduler metrics server error: {}", e);
+            }
+        });
+
         // Start main processing loop
         let processing_task = self.start_processing_loop();
 
         // Wait for both tasks
         tokio::try_join!(
             async { dashboard_task.await.map_err(|e| anyhow::anyhow!("Dashboard task error: {}", e)) },
+            async { metrics_task.await.map_err(|e| anyhow::anyhow!("Metrics task error: {}", e)) },
             processing_task
         )?;
 
@@ -145,22 +173,46 @@ impl MainApplication {
 
     async fn start_processing_loop(&mut self) -> Result<()> {
         let mut interval = interval(Duration::from_secs(self.config.check_interval_minutes * 60));
+        let shutdown_notify = self.scheduler.shutdown_notify();
 
         info!("🔄 Starting main processing loop (check every {} minutes)", self.config.check_interval_minutes);
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown_notify.notified() => {
+                    info!("🛑 Shutdown requested, stopping processing loop");
+                    return Ok(());
+                }
+            }
 
             let current_mode = self.scheduler.get_current_mode().await;
 
+            // Each worker runs under `catch_unwind` so a panic mid-session is logged and
+            // the daemon keeps running for the next cycle instead of taking the whole
+            // process down with it.
             match current_mode.as_str() {
                 "backlink_processing" => {
                     info!("🔗 Entering backlink discovery mode");
-                    self.run_backlink_processing().await?;
+                    match AssertUnwindSafe(self.run_backlink_processing()).catch_unwind().await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => error!("Backlink processing failed: {}", e),
+                        Err(payload) => error!(
+                            "🔥 Backlink processing worker panicked ({}); respawning it next cycle",
+                            panic_message(&payload)
+                        ),
+                    }
                 }
                 "crawling" => {
                     info!("🕸️  Entering web crawling mode");
-                    self.run_web_crawling().await?;
+                    match AssertUnwindSafe(self.run_web_crawling()).catch_unwind().await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => error!("Web crawling failed: {}", e),
+                        Err(payload) => error!(
+                            "🔥 Web crawling worker panicked ({}); respawning it next cycle",
+                            panic_message(&payload)
+                        ),
+                    }
                 }
                 _ => {
                     info!("⏸️  Idle mode - waiting for next scheduled activity");
@@ -170,12 +222,18 @@ impl MainApplication {
 
             // Update dashboard statistics
             self.update_dashboard_stats().await?;
+
+            if self.scheduler.is_shutdown_requested() {
+                info!("🛑 Shutdown requested, exiting processing loop after finishing this cycle");
+                return Ok(());
+            }
         }
     }
 
     async fn run_backlink_processing(&mut self) -> Result<()> {
         info!("🔗 Starting 2-hour backlink processing session...");
 
+        let session_id = self.database.start_processing_session("backlink_discovery").await?;
         let start_time = Utc::now();
         let backlinks_found = self.backlink_processor.process_backlinks_for_duration(2).await?;
         let end_time = Utc::now();
@@ -184,8 +242,30 @@ impl MainApplication {
         info!("   Duration: {} minutes", (end_time - start_time).num_minutes());
         info!("   Backlinks found: {}", backlinks_found);
 
+        self.scheduler.record_crawl_completed(0, backlinks_found as u64, 0).await;
+
         // Save processing session info
-        self.save_processing_session("backlink_discovery", backlinks_found as i32, None).await?;
+        self.database
+            .complete_processing_session(session_id, backlinks_found as i32, 0, "completed")
+            .await?;
+
+        // Recompute PageRank over the (possibly just-grown) backlink graph so domain
+        // authority stays in sync with what was just discovered
+        match self.database.compute_pagerank(0.85, 100, 1e-6).await {
+            Ok(scores) => info!("📈 Recomputed PageRank for {} urls", scores.len()),
+            Err(e) => warn!("Failed to recompute PageRank: {}", e),
+        }
+
+        // Clear out previously discovered backlinks that now fall under the weed list
+        if !self.config.crawler_config.weed_domains.is_empty() {
+            match self.database.prune_weeded_backlinks(&self.config.crawler_config.weed_domains) {
+                Ok(removed) if removed > 0 => {
+                    info!("🧹 Pruned {} previously stored backlinks from weeded domains", removed)
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to prune weeded backlinks: {}", e),
+            }
+        }
 
         Ok(())
     }
@@ -199,27 +279,75 @@ impl MainApplication {
             return Ok(());
         }
 
-        let urls: Vec<String> = seed_urls.iter().map(|s| s.url.clone()).collect();
+        // Skip URLs we fetched within the cache TTL; just bump their seed-url bookkeeping
+        // instead of paying for a full crawl/extraction pass again.
+        let mut urls = Vec::new();
+        for seed in &seed_urls {
+            if self.url_cache.get(&seed.url).is_some() {
+                info!("⏭️  Skipping {} (cached, still fresh)", seed.url);
+                if let Err(e) = self.database.update_seed_url_crawled(&seed.url).await {
+                    warn!("Failed to bump cached seed URL {}: {}", seed.url, e);
+                }
+            } else {
+                urls.push(seed.url.clone());
+            }
+        }
+
+        if urls.is_empty() {
+            info!("⏭️  All seed URLs served from cache, skipping crawling session");
+            return Ok(());
+        }
         info!("📝 Crawling {} URLs", urls.len());
 
+        let session_id = self.database.start_processing_session("web_crawling").await?;
         let start_time = Utc::now();
-        let result = self.crawler.crawl(urls, &self.database).await?;
+        let result = self.crawler.crawl(urls.clone(), &self.database).await?;
         let end_time = Utc::now();
 
+        for url in &urls {
+            if let Err(e) = self.url_cache.put(url, &CacheEntry {
+                content_hash: String::new(),
+                etag: String::new(),
+                last_modified: String::new(),
+                fetched_at: end_time,
+            }) {
+                warn!("Failed to cache fetch result for {}: {}", url, e);
+            }
+        }
+
         info!("✅ Web crawling completed!");
         info!("   Duration: {} minutes", (end_time - start_time).num_minutes());
         info!("   Pages crawled: {}", result.pages_crawled.unwrap_or(0));
         info!("   Errors: {}", result.errors.unwrap_or(0));
 
-        // Save processing session info
-        self.save_processing_session("web_crawling", result.pages_crawled.unwrap_or(0) as i32, result.errors).await?;
+        self.scheduler.record_crawl_completed(
+            result.pages_crawled.unwrap_or(0) as u64,
+            0,
+            result.errors.unwrap_or(0) as u64,
+        ).await;
 
-        Ok(())
-    }
+        // Save processing session info
+        let status = if result.errors.unwrap_or(0) > 0 { "failed" } else { "completed" };
+        self.database
+            .complete_processing_session(
+                session_id,
+                result.pages_crawled.unwrap_or(0) as i32,
+                result.errors.unwrap_or(0) as i32,
+                status,
+            )
+            .await?;
+
+        // Clear out previously stored pages that now fall under the weed list
+        if !self.config.web_crawl_config.weed_domains.is_empty() {
+            match self.database.prune_weeded_pages(&self.config.web_crawl_config.weed_domains) {
+                Ok(removed) if removed > 0 => {
+                    info!("🧹 Pruned {} previously stored pages from weeded domains", removed)
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to prune weeded pages: {}", e),
+            }
+        }
 
-    async fn save_processing_session(&self, session_type: &str, items_processed: i32, errors: Option<usize>) -> Result<()> {
-        // TODO: Implement session logging in database
-        info!("💾 Session saved: {} processed {} items", session_type, items_processed);
         Ok(())
     }
 
@@ -244,24 +372,27 @@ impl MainApplication {
     }
 
     async fn update_dashboard_stats(&self) -> Result<()> {
-        let current_mode = self.scheduler.get_current_mode().await;
-        let next_switch = Utc::now(); // TODO: Implement next_mode_switch_time
-
-        let stats = DashboardStats {
-            total_urls_crawled: 0, // TODO: Get from database
-            total_backlinks_found: 0, // TODO: Get from database
-            unique_domains: 0, // TODO: Get from database
-            crawl_rate_per_hour: 0.0,
-            backlink_rate_per_hour: 0.0,
-            database_size_mb: 0.0,
-            system_memory_usage: 0.0,
-            system_cpu_usage: 0.0,
-            current_mode,
-            next_mode_switch: next_switch,
-            last_updated: Utc::now(),
-        };
+        let mut stats = self.database.get_dashboard_stats().await?;
+
+        let mut system = System::new_all();
+        system.refresh_all();
+        stats.system_memory_usage = (system.used_memory() as f64 / system.total_memory() as f64) * 100.0;
+        stats.system_cpu_usage = system.global_cpu_info().cpu_usage() as f64;
+        stats.current_mode = self.scheduler.get_current_mode().await;
+        stats.last_updated = Utc::now();
+
+        self.database.update_stats(&stats).await?;
 
-        // TODO: Update dashboard with stats
         Ok(())
     }
 }
+
+/// Extracts a human-readable message from a `catch_unwind` payload, falling back to a
+/// generic description for panics that didn't pass a `&str`/`String` payload.
+fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
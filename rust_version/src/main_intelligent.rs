@@ -7,9 +7,11 @@ mod backlink_processor;
 mod crawler;
 mod scheduler;
 mod dashboard;
+mod cache;
 mod main_app;
 
 use models::{CrawlerConfig, CrawlConfig};
+use cache::CacheConfig;
 use main_app::{MainApplication, MainApplicationConfig};
 
 #[tokio::main]
@@ -56,6 +58,20 @@ async fn run_intelligent_crawler() -> Result<(), Box<dyn std::error::Error>> {
             ],
             max_depth: 5,
             enable_javascript: false,
+            allowed_domains: Vec::new(),
+            weed_domains: Vec::new(),
+            proxies: Vec::new(),
+            requests_per_second_per_domain: 2.0,
+            burst: 5,
+            max_requests_per_host_per_second: 2.0,
+            max_redirects: 10,
+            max_body_bytes: 4 * 1024 * 1024,
+            max_fetch_duration_secs: 10,
+            min_language_confidence: 0.7,
+            page_budget: None,
+            links_per_page_budget: None,
+            max_level: None,
+            accepted_content_types: vec!["text/html".to_string(), "text/plain".to_string()],
         },
         web_crawl_config: CrawlConfig {
             user_agent: "RatCrawler/2.0 (Intelligent Web Crawler)".to_string(),
@@ -65,7 +81,18 @@ async fn run_intelligent_crawler() -> Result<(), Box<dyn std::error::Error>> {
             max_pages: 1000,
             delay_ms: 500,
             respect_robots_txt: true,
+            max_body_bytes: 4 * 1024 * 1024,
+            fetch_timeout_secs: 10,
+            max_concurrency: 8,
+            allowed_domains: Vec::new(),
+            weed_domains: Vec::new(),
+            respect_meta_robots: true,
+            page_budget: None,
+            links_per_page_budget: None,
+            max_level: None,
+            accepted_content_types: vec!["text/html".to_string(), "text/plain".to_string()],
         },
+        cache_config: CacheConfig::default(),
     };
 
     let mut app = MainApplication::new(config).await?;
@@ -56,55 +56,74 @@ async fn main() -> Result<()> {
         })
     };
 
-    // Start main processing loop
+    // Start main processing loop, supervised so a panic mid-crawl doesn't permanently lose
+    // this worker for the rest of the daemon's lifetime.
     let processing_handle = {
         let crawler = crawler.clone();
         let backlink_processor = backlink_processor.clone();
-        let schedule_manager = schedule_manager.clone();
+        let schedule_manager_for_loop = schedule_manager.clone();
         let database = database.clone();
 
-        tokio::spawn(async move {
-            loop {
-                let current_mode = schedule_manager.get_current_mode().await;
-
-                match current_mode.as_str() {
-                    "backlink_processing" => {
-                        info!("Starting backlink processing mode for 2 hours");
-                        match backlink_processor.process_backlinks_for_duration(2).await {
-                            Ok(count) => info!("Backlink processing completed. Found {} backlinks", count),
-                            Err(e) => error!("Backlink processing failed: {}", e),
+        schedule_manager.spawn_supervised("processing_loop", move || {
+            let crawler = crawler.clone();
+            let backlink_processor = backlink_processor.clone();
+            let schedule_manager = schedule_manager_for_loop.clone();
+            let database = database.clone();
+            async move {
+                loop {
+                    let current_mode = schedule_manager.get_current_mode().await;
+
+                    match current_mode.as_str() {
+                        "backlink_processing" => {
+                            info!("Starting backlink processing mode for 2 hours");
+                            match backlink_processor.process_backlinks_for_duration(2).await {
+                                Ok(count) => {
+                                    info!("Backlink processing completed. Found {} backlinks", count);
+                                    schedule_manager.record_crawl_completed(0, count as u64, 0).await;
+                                }
+                                Err(e) => {
+                                    error!("Backlink processing failed: {}", e);
+                                    schedule_manager.record_crawl_completed(0, 0, 1).await;
+                                }
+                            }
                         }
-                    }
-                    "crawling" => {
-                        info!("Starting crawling mode");
-                        // Calculate hours until next backlink processing
-                        let next_switch = schedule_manager.get_next_mode_switch().await;
-                        let hours_until_switch = (next_switch - Utc::now()).num_hours().max(1) as u64;
-
-                        match crawler.crawl_for_duration(hours_until_switch).await {
-                            Ok(count) => info!("Crawling completed. Crawled {} URLs", count),
-                            Err(e) => error!("Crawling failed: {}", e),
+                        "crawling" => {
+                            info!("Starting crawling mode");
+                            // Calculate hours until next backlink processing
+                            let next_switch = schedule_manager.get_next_mode_switch().await;
+                            let hours_until_switch = (next_switch - Utc::now()).num_hours().max(1) as u64;
+
+                            match crawler.crawl_for_duration(hours_until_switch).await {
+                                Ok(count) => {
+                                    info!("Crawling completed. Crawled {} URLs", count);
+                                    schedule_manager.record_crawl_completed(count as u64, 0, 0).await;
+                                }
+                                Err(e) => {
+                                    error!("Crawling failed: {}", e);
+                                    schedule_manager.record_crawl_completed(0, 0, 1).await;
+                                }
+                            }
+                        }
+                        "idle" => {
+                            info!("System in idle mode, waiting 60 seconds");
+                            tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+                        }
+                        _ => {
+                            error!("Unknown mode: {}", current_mode);
+                            tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
                         }
                     }
-                    "idle" => {
-                        info!("System in idle mode, waiting 60 seconds");
-                        tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
-                    }
-                    _ => {
-                        error!("Unknown mode: {}", current_mode);
-                        tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
-                    }
-                }
 
-                // Update database stats
-                if let Ok(stats) = database.get_dashboard_stats().await {
-                    if let Err(e) = database.update_stats(&stats).await {
-                        error!("Failed to update stats: {}", e);
+                    // Update database stats
+                    if let Ok(stats) = database.get_dashboard_stats().await {
+                        if let Err(e) = database.update_stats(&stats).await {
+                            error!("Failed to update stats: {}", e);
+                        }
                     }
-                }
 
-                // Small delay before checking mode again
-                tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
+                    // Small delay before checking mode again
+                    tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
+                }
             }
         })
     };
@@ -119,6 +138,16 @@ async fn main() -> Result<()> {
         })
     };
 
+    // Start scheduler metrics server (no-op if ScheduleConfig.metrics_port is unset)
+    let metrics_handle = {
+        let schedule_manager = schedule_manager.clone();
+        tokio::spawn(async move {
+            if let Err(e) = schedule_manager.start_metrics_server().await {
+                error!("Scheduler metrics server error: {}", e);
+            }
+        })
+    };
+
     info!("All services started successfully!");
     info!("Dashboard available at: http://localhost:8080");
 
@@ -130,6 +159,7 @@ async fn main() -> Result<()> {
     dashboard_handle.abort();
     processing_handle.abort();
     scheduler_handle.abort();
+    metrics_handle.abort();
 
     info!("RatCrawler shutdown complete");
     Ok(())
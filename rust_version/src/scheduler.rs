@@ -1,27 +1,237 @@
 use crate::models::{ScheduleConfig, SystemHealth};
 use chrono::{DateTime, Utc, Timelike};
 use log::{info, warn, error};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 use tokio::time::{sleep, Duration};
+use warp::Filter;
+
+/// Rolling event counters backing `SystemHealth` and the `/metrics` Prometheus endpoint.
+/// Totals are monotonic atomics; `errors_last_hour`/`warnings_last_hour` are derived from
+/// timestamp queues that are trimmed to the last hour on every read.
+#[derive(Default)]
+pub struct Metrics {
+    pages_crawled_total: AtomicU64,
+    crawl_errors_total: AtomicU64,
+    backlinks_found_total: AtomicU64,
+    error_timestamps: RwLock<VecDeque<DateTime<Utc>>>,
+    warning_timestamps: RwLock<VecDeque<DateTime<Utc>>>,
+}
+
+impl Metrics {
+    pub fn record_pages_crawled(&self, count: u64) {
+        self.pages_crawled_total.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn record_backlinks_found(&self, count: u64) {
+        self.backlinks_found_total.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub async fn record_error(&self) {
+        self.crawl_errors_total.fetch_add(1, Ordering::Relaxed);
+        self.error_timestamps.write().await.push_back(Utc::now());
+    }
+
+    pub async fn record_warning(&self) {
+        self.warning_timestamps.write().await.push_back(Utc::now());
+    }
+
+    async fn trim_and_count(queue: &RwLock<VecDeque<DateTime<Utc>>>) -> i32 {
+        let cutoff = Utc::now() - chrono::Duration::hours(1);
+        let mut queue = queue.write().await;
+        while queue.front().map_or(false, |t| *t < cutoff) {
+            queue.pop_front();
+        }
+        queue.len() as i32
+    }
+
+    pub async fn errors_last_hour(&self) -> i32 {
+        Self::trim_and_count(&self.error_timestamps).await
+    }
+
+    pub async fn warnings_last_hour(&self) -> i32 {
+        Self::trim_and_count(&self.warning_timestamps).await
+    }
+
+    pub fn pages_crawled_total(&self) -> u64 {
+        self.pages_crawled_total.load(Ordering::Relaxed)
+    }
+
+    pub fn crawl_errors_total(&self) -> u64 {
+        self.crawl_errors_total.load(Ordering::Relaxed)
+    }
+
+    pub fn backlinks_found_total(&self) -> u64 {
+        self.backlinks_found_total.load(Ordering::Relaxed)
+    }
+}
 
 pub struct ScheduleManager {
     config: ScheduleConfig,
     current_mode: Arc<RwLock<String>>,
     system_start_time: DateTime<Utc>,
+    metrics: Arc<Metrics>,
+    last_mode_switch: Arc<RwLock<DateTime<Utc>>>,
+    http_client: reqwest::Client,
+    shutdown_requested: Arc<AtomicBool>,
+    shutdown_notify: Arc<Notify>,
 }
 
 impl ScheduleManager {
     pub fn new(config: ScheduleConfig) -> Self {
+        let system_start_time = Utc::now();
         Self {
             config,
             current_mode: Arc::new(RwLock::new("idle".to_string())),
-            system_start_time: Utc::now(),
+            system_start_time,
+            metrics: Arc::new(Metrics::default()),
+            last_mode_switch: Arc::new(RwLock::new(system_start_time)),
+            http_client: reqwest::Client::new(),
+            shutdown_requested: Arc::new(AtomicBool::new(false)),
+            shutdown_notify: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    /// A clone of the `Notify` flipped by `request_shutdown`, for callers (e.g. a
+    /// processing loop owned by `MainApplication`) that want to wake immediately on
+    /// shutdown instead of polling `is_shutdown_requested`.
+    pub fn shutdown_notify(&self) -> Arc<Notify> {
+        self.shutdown_notify.clone()
+    }
+
+    pub fn is_shutdown_requested(&self) -> bool {
+        self.shutdown_requested.load(Ordering::SeqCst)
+    }
+
+    /// Flips the shutdown flag and wakes everyone waiting on `shutdown_notify`. Idempotent.
+    pub fn request_shutdown(&self) {
+        if !self.shutdown_requested.swap(true, Ordering::SeqCst) {
+            info!("Shutdown requested");
         }
+        self.shutdown_notify.notify_waiters();
     }
 
-    pub async fn start(&self) -> anyhow::Result<()> {
+    /// Installs SIGINT/SIGTERM handlers that call `request_shutdown` the first time either
+    /// fires, so `start` (and anything else watching `shutdown_notify`) can finish its
+    /// current work and exit cleanly instead of being killed outright.
+    pub fn install_signal_handlers(self: &Arc<Self>) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            #[cfg(unix)]
+            {
+                use tokio::signal::unix::{signal, SignalKind};
+                let mut sigterm = match signal(SignalKind::terminate()) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        error!("Failed to install SIGTERM handler: {}", e);
+                        return;
+                    }
+                };
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => info!("Received SIGINT"),
+                    _ = sigterm.recv() => info!("Received SIGTERM"),
+                }
+            }
+            #[cfg(not(unix))]
+            {
+                let _ = tokio::signal::ctrl_c().await;
+                info!("Received Ctrl+C");
+            }
+            manager.request_shutdown();
+        });
+    }
+
+    /// Spawns `task_fn` under supervision: if the spawned task panics, the panic is logged
+    /// and the task is respawned immediately rather than silently losing that worker's
+    /// capacity for the rest of the daemon's lifetime. Stops respawning once shutdown has
+    /// been requested.
+    pub fn spawn_supervised<F, Fut>(self: &Arc<Self>, name: &str, task_fn: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let manager = self.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            loop {
+                if manager.is_shutdown_requested() {
+                    info!("Supervisor for '{}' stopping: shutdown requested", name);
+                    break;
+                }
+
+                match tokio::spawn(task_fn()).await {
+                    Ok(()) => {
+                        if manager.is_shutdown_requested() {
+                            break;
+                        }
+                        warn!("Supervised task '{}' exited; respawning", name);
+                    }
+                    Err(e) if e.is_panic() => {
+                        error!("Supervised task '{}' panicked: {}; respawning", name, e);
+                    }
+                    Err(e) => {
+                        error!("Supervised task '{}' was cancelled: {}", name, e);
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Records the outcome of a crawl or backlink-processing run the caller just completed,
+    /// then pings `heartbeat_url` (if configured) so external uptime monitors see activity.
+    pub async fn record_crawl_completed(&self, pages_crawled: u64, backlinks_found: u64, errors: u64) {
+        self.metrics.record_pages_crawled(pages_crawled);
+        self.metrics.record_backlinks_found(backlinks_found);
+        for _ in 0..errors {
+            self.metrics.record_error().await;
+        }
+        self.ping_heartbeat().await;
+    }
+
+    async fn ping_heartbeat(&self) {
+        if let Some(url) = &self.config.heartbeat_url {
+            if let Err(e) = self.http_client.get(url).send().await {
+                warn!("Heartbeat ping to {} failed: {}", url, e);
+            }
+        }
+    }
+
+    /// Serves `Metrics` in Prometheus text exposition format on `self.config.metrics_port`.
+    /// Runs until the process exits; callers spawn this alongside `start`.
+    pub async fn start_metrics_server(self: Arc<Self>) -> anyhow::Result<()> {
+        let Some(port) = self.config.metrics_port else {
+            info!("No metrics_port configured, metrics server disabled");
+            return Ok(());
+        };
+
+        let manager = self.clone();
+        let metrics_route = warp::path("metrics")
+            .and(warp::path::end())
+            .and_then(move || {
+                let manager = manager.clone();
+                async move { render_metrics(manager).await }
+            });
+
+        info!("Starting scheduler metrics server on port {}", port);
+        warp::serve(metrics_route).run(([0, 0, 0, 0], port)).await;
+        Ok(())
+    }
+
+    /// Runs the mode-switching loop until SIGINT/SIGTERM (or another caller of
+    /// `request_shutdown`) asks it to stop; each iteration finishes before the shutdown
+    /// check is applied, so a mode switch in progress is never torn down mid-way.
+    pub async fn start(self: Arc<Self>) -> anyhow::Result<()> {
         info!("Starting schedule manager with config: {:?}", self.config);
+        self.install_signal_handlers();
 
         loop {
             let current_time = Utc::now();
@@ -42,11 +252,25 @@ impl ScheduleManager {
             if *current_mode != new_mode {
                 info!("Switching mode from {} to {}", *current_mode, new_mode);
                 *current_mode = new_mode.to_string();
+                *self.last_mode_switch.write().await = current_time;
             }
             drop(current_mode);
 
-            // Check every minute
-            sleep(Duration::from_secs(60)).await;
+            self.ping_heartbeat().await;
+
+            if self.is_shutdown_requested() {
+                info!("Schedule manager shutting down");
+                return Ok(());
+            }
+
+            // Check every minute, but wake immediately if shutdown is requested mid-wait.
+            tokio::select! {
+                _ = sleep(Duration::from_secs(60)) => {}
+                _ = self.shutdown_notify.notified() => {
+                    info!("Schedule manager shutting down");
+                    return Ok(());
+                }
+            }
         }
     }
 
@@ -111,8 +335,8 @@ impl ScheduleManager {
             backlink_processor_status: "running".to_string(),
             scheduler_status: "running".to_string(),
             uptime_seconds: uptime,
-            errors_last_hour: 0, // TODO: Implement error counting
-            warnings_last_hour: 0, // TODO: Implement warning counting
+            errors_last_hour: self.metrics.errors_last_hour().await,
+            warnings_last_hour: self.metrics.warnings_last_hour().await,
         }
     }
 
@@ -126,3 +350,43 @@ impl ScheduleManager {
         self.config.crawling_hours.contains(&current_hour)
     }
 }
+
+/// Renders `Metrics` plus the current mode as Prometheus text exposition format, in the
+/// same style as `dashboard::get_metrics`.
+async fn render_metrics(manager: Arc<ScheduleManager>) -> Result<impl warp::Reply, Infallible> {
+    let mode = manager.get_current_mode().await;
+    let mode_label = |label: &str| if mode == label { 1 } else { 0 };
+
+    let body = format!(
+        "# HELP ratcrawler_scheduler_pages_crawled_total Total pages crawled across all scheduled runs.\n\
+         # TYPE ratcrawler_scheduler_pages_crawled_total counter\n\
+         ratcrawler_scheduler_pages_crawled_total {}\n\
+         # HELP ratcrawler_scheduler_backlinks_found_total Total backlinks discovered across all scheduled runs.\n\
+         # TYPE ratcrawler_scheduler_backlinks_found_total counter\n\
+         ratcrawler_scheduler_backlinks_found_total {}\n\
+         # HELP ratcrawler_scheduler_crawl_errors_total Total crawl/backlink errors recorded.\n\
+         # TYPE ratcrawler_scheduler_crawl_errors_total counter\n\
+         ratcrawler_scheduler_crawl_errors_total {}\n\
+         # HELP ratcrawler_scheduler_mode_idle 1 when the scheduler is currently idle.\n\
+         # TYPE ratcrawler_scheduler_mode_idle gauge\n\
+         ratcrawler_scheduler_mode_idle {}\n\
+         # HELP ratcrawler_scheduler_mode_crawling 1 when the scheduler is currently crawling.\n\
+         # TYPE ratcrawler_scheduler_mode_crawling gauge\n\
+         ratcrawler_scheduler_mode_crawling {}\n\
+         # HELP ratcrawler_scheduler_mode_backlink_processing 1 when the scheduler is currently processing backlinks.\n\
+         # TYPE ratcrawler_scheduler_mode_backlink_processing gauge\n\
+         ratcrawler_scheduler_mode_backlink_processing {}\n\
+         # HELP ratcrawler_scheduler_last_mode_switch_timestamp_seconds Unix timestamp of the last mode switch.\n\
+         # TYPE ratcrawler_scheduler_last_mode_switch_timestamp_seconds gauge\n\
+         ratcrawler_scheduler_last_mode_switch_timestamp_seconds {}\n",
+        manager.metrics.pages_crawled_total(),
+        manager.metrics.backlinks_found_total(),
+        manager.metrics.crawl_errors_total(),
+        mode_label("idle"),
+        mode_label("crawling"),
+        mode_label("backlink_processing"),
+        manager.last_mode_switch.read().await.timestamp(),
+    );
+
+    Ok(warp::reply::with_header(body, "Content-Type", "text/plain; version=0.0.4"))
+}